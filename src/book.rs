@@ -0,0 +1,275 @@
+use crate::{ClientResult, OrderBookSummary, OrderSummary, Side};
+use anyhow::Context;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// One level update of a `price_change` market channel event. A size of zero
+/// removes the level.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct PriceChange {
+    /// Set in the batched format, where one event covers several tokens.
+    #[serde(default)]
+    pub asset_id: Option<String>,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub price: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub size: Decimal,
+    pub side: Side,
+}
+
+/// A `price_change` event, either per token with `changes` or batched with
+/// `price_changes` carrying their own `asset_id`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct PriceChangeEvent {
+    #[serde(default)]
+    pub asset_id: Option<String>,
+    #[serde(default)]
+    pub market: Option<String>,
+    #[serde(default)]
+    pub changes: Vec<PriceChange>,
+    #[serde(default)]
+    pub price_changes: Vec<PriceChange>,
+    #[serde(default)]
+    pub hash: Option<String>,
+}
+
+impl PriceChangeEvent {
+    /// Changes that apply to `asset_id`.
+    pub fn changes_for<'a>(&'a self, asset_id: &'a str) -> impl Iterator<Item = &'a PriceChange> {
+        let own = self.asset_id.as_deref() == Some(asset_id);
+        self.changes.iter().filter(move |_| own).chain(
+            self.price_changes
+                .iter()
+                .filter(move |c| c.asset_id.as_deref() == Some(asset_id)),
+        )
+    }
+}
+
+/// A `tick_size_change` event, sent when a market's minimum tick size changes,
+/// typically as its price nears 0 or 1.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TickSizeChangeEvent {
+    pub asset_id: String,
+    #[serde(default)]
+    pub market: Option<String>,
+    pub old_tick_size: Decimal,
+    pub new_tick_size: Decimal,
+}
+
+/// Order book of one token kept current from a snapshot plus `price_change` diffs.
+#[derive(Debug, Clone, Default)]
+pub struct LocalOrderBook {
+    pub asset_id: String,
+    pub market: String,
+    pub hash: String,
+    bids: BTreeMap<Decimal, Decimal>,
+    asks: BTreeMap<Decimal, Decimal>,
+}
+
+impl LocalOrderBook {
+    pub fn new(snapshot: &OrderBookSummary) -> Self {
+        let mut book = Self::default();
+        book.apply_snapshot(snapshot);
+        book
+    }
+
+    /// Replaces the whole book, e.g. on a `book` event or after a resync.
+    pub fn apply_snapshot(&mut self, snapshot: &OrderBookSummary) {
+        self.asset_id = snapshot.asset_id.clone();
+        self.market = snapshot.market.clone();
+        self.hash = snapshot.hash.clone();
+        self.bids = levels(&snapshot.bids);
+        self.asks = levels(&snapshot.asks);
+    }
+
+    /// Sets the size resting at `price`; zero removes the level.
+    pub fn apply_change(&mut self, side: Side, price: Decimal, size: Decimal) {
+        let levels = match side {
+            Side::BUY => &mut self.bids,
+            Side::SELL => &mut self.asks,
+        };
+        if size.is_zero() {
+            levels.remove(&price);
+        } else {
+            levels.insert(price, size);
+        }
+    }
+
+    /// Applies the changes of `event` concerning this book's token.
+    pub fn apply_price_change(&mut self, event: &PriceChangeEvent) {
+        let changes = event
+            .changes_for(&self.asset_id)
+            .map(|c| (c.side, c.price, c.size))
+            .collect::<Vec<_>>();
+        if changes.is_empty() {
+            return;
+        }
+        for (side, price, size) in changes {
+            self.apply_change(side, price, size);
+        }
+        if let Some(hash) = &event.hash {
+            self.hash = hash.clone();
+        }
+    }
+
+    /// Applies a raw market channel event; `book` and `price_change` events
+    /// update the book, others are ignored.
+    pub fn apply_event(&mut self, event: &Value) -> ClientResult<()> {
+        match event["event_type"].as_str() {
+            Some("book") if event["asset_id"].as_str() == Some(&self.asset_id) => {
+                let snapshot =
+                    OrderBookSummary::deserialize(event).context("Invalid book event")?;
+                self.apply_snapshot(&snapshot);
+            }
+            Some("price_change") => {
+                let diff =
+                    PriceChangeEvent::deserialize(event).context("Invalid price_change event")?;
+                self.apply_price_change(&diff);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Bids, best (highest) first.
+    pub fn bids(&self) -> impl Iterator<Item = OrderSummary> + '_ {
+        self.bids
+            .iter()
+            .rev()
+            .map(|(&price, &size)| OrderSummary { price, size })
+    }
+
+    /// Asks, best (lowest) first.
+    pub fn asks(&self) -> impl Iterator<Item = OrderSummary> + '_ {
+        self.asks
+            .iter()
+            .map(|(&price, &size)| OrderSummary { price, size })
+    }
+
+    pub fn best_bid(&self) -> Option<OrderSummary> {
+        self.bids().next()
+    }
+
+    pub fn best_ask(&self) -> Option<OrderSummary> {
+        self.asks().next()
+    }
+
+    pub fn midpoint(&self) -> Option<Decimal> {
+        Some((self.best_bid()?.price + self.best_ask()?.price) / Decimal::TWO)
+    }
+}
+
+fn levels(orders: &[OrderSummary]) -> BTreeMap<Decimal, Decimal> {
+    orders
+        .iter()
+        .filter(|o| !o.size.is_zero())
+        .map(|o| (o.price, o.size))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn dec(s: &str) -> Decimal {
+        Decimal::from_str(s).unwrap()
+    }
+
+    fn snapshot() -> OrderBookSummary {
+        serde_json::from_value(serde_json::json!({
+            "market": "0xcond",
+            "asset_id": "1",
+            "hash": "0xsnap",
+            "timestamp": "1700000000000",
+            "bids": [
+                {"price": "0.45", "size": "100"},
+                {"price": "0.48", "size": "20"}
+            ],
+            "asks": [
+                {"price": "0.55", "size": "40"},
+                {"price": "0.52", "size": "10"}
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_snapshot_and_diffs() {
+        let mut book = LocalOrderBook::new(&snapshot());
+        assert_eq!(book.best_bid().unwrap().price, dec("0.48"));
+        assert_eq!(book.best_ask().unwrap().price, dec("0.52"));
+        assert_eq!(book.midpoint(), Some(dec("0.50")));
+
+        let diff: PriceChangeEvent = serde_json::from_value(serde_json::json!({
+            "event_type": "price_change",
+            "asset_id": "1",
+            "market": "0xcond",
+            "hash": "0xdiff1",
+            "changes": [
+                {"price": "0.49", "side": "BUY", "size": "5"},
+                {"price": "0.52", "side": "SELL", "size": "0"},
+                {"price": "0.45", "side": "BUY", "size": "60"}
+            ]
+        }))
+        .unwrap();
+        book.apply_price_change(&diff);
+
+        let bids: Vec<_> = book.bids().map(|o| (o.price, o.size)).collect();
+        assert_eq!(
+            bids,
+            [
+                (dec("0.49"), dec("5")),
+                (dec("0.48"), dec("20")),
+                (dec("0.45"), dec("60"))
+            ]
+        );
+        assert_eq!(
+            book.best_ask().unwrap(),
+            OrderSummary {
+                price: dec("0.55"),
+                size: dec("40")
+            }
+        );
+        assert_eq!(book.midpoint(), Some(dec("0.52")));
+        assert_eq!(book.hash, "0xdiff1");
+
+        // Batched format, including a change for another token that must be skipped.
+        book.apply_event(&serde_json::json!({
+            "event_type": "price_change",
+            "market": "0xcond",
+            "price_changes": [
+                {"asset_id": "1", "price": "0.50", "side": "SELL", "size": "7"},
+                {"asset_id": "2", "price": "0.10", "side": "BUY", "size": "9"}
+            ]
+        }))
+        .unwrap();
+        assert_eq!(book.best_ask().unwrap().price, dec("0.50"));
+        assert_eq!(book.best_bid().unwrap().price, dec("0.49"));
+        assert_eq!(book.midpoint(), Some(dec("0.495")));
+    }
+
+    #[test]
+    fn test_book_event_resets_levels() {
+        let mut book = LocalOrderBook::new(&snapshot());
+        book.apply_change(Side::BUY, dec("0.30"), dec("1"));
+
+        book.apply_event(&serde_json::json!({
+            "event_type": "book",
+            "asset_id": "1",
+            "market": "0xcond",
+            "hash": "0xnew",
+            "timestamp": "1700000000001",
+            "bids": [{"price": "0.40", "size": "3"}],
+            "asks": []
+        }))
+        .unwrap();
+
+        assert_eq!(book.bids().count(), 1);
+        assert_eq!(book.best_ask(), None);
+        assert_eq!(book.midpoint(), None);
+        assert_eq!(book.hash, "0xnew");
+    }
+}
@@ -0,0 +1,209 @@
+use anyhow::Result;
+use dashmap::DashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+
+/// Per-token cache for values that rarely change, such as tick size and neg risk.
+///
+/// Backed by a sharded map so that concurrent readers never block each other;
+/// writes only happen on a cache miss. Concurrent misses for the same token
+/// share a single fetch.
+#[derive(Debug, Default)]
+pub struct TokenCache<V> {
+    entries: DashMap<String, V>,
+    in_flight: DashMap<String, Arc<OnceCell<V>>>,
+}
+
+impl<V: Copy> TokenCache<V> {
+    pub fn new() -> Self {
+        Self {
+            entries: DashMap::new(),
+            in_flight: DashMap::new(),
+        }
+    }
+
+    /// Returns the cached value or runs `fetch` to fill it. Callers missing on
+    /// the same token while a fetch is running wait for its result instead of
+    /// issuing their own. A failed fetch is not cached.
+    pub async fn get_or_fetch<F, Fut>(&self, token_id: &str, fetch: F) -> Result<V>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V>>,
+    {
+        if let Some(v) = self.get(token_id) {
+            return Ok(v);
+        }
+
+        let cell = self
+            .in_flight
+            .entry(token_id.to_owned())
+            .or_default()
+            .clone();
+        let result = cell
+            .get_or_try_init(|| async {
+                // The previous flight may have finished between the miss above and
+                // joining this cell.
+                match self.get(token_id) {
+                    Some(v) => Ok(v),
+                    None => fetch().await,
+                }
+            })
+            .await
+            .copied();
+
+        if let Ok(v) = result {
+            self.insert(token_id, v);
+        }
+        self.in_flight
+            .remove_if(token_id, |_, c| Arc::ptr_eq(c, &cell));
+        result
+    }
+
+    pub fn get(&self, token_id: &str) -> Option<V> {
+        self.entries.get(token_id).map(|v| *v)
+    }
+
+    pub fn insert(&self, token_id: &str, value: V) {
+        self.entries.insert(token_id.to_owned(), value);
+    }
+
+    pub fn remove(&self, token_id: &str) -> Option<V> {
+        self.entries.remove(token_id).map(|(_, v)| v)
+    }
+
+    pub fn clear(&self) {
+        self.entries.clear();
+    }
+}
+
+/// Cache whose entries expire `ttl` after insertion, for data that changes
+/// rarely but not never, such as markets.
+#[derive(Debug)]
+pub struct TtlCache<V> {
+    ttl: Duration,
+    entries: DashMap<String, (Instant, V)>,
+}
+
+impl<V: Clone> TtlCache<V> {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: DashMap::new(),
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub fn get(&self, key: &str) -> Option<V> {
+        let entry = self.entries.get(key)?;
+        let (inserted, value) = &*entry;
+        if inserted.elapsed() < self.ttl {
+            return Some(value.clone());
+        }
+        drop(entry);
+        self.entries
+            .remove_if(key, |_, (inserted, _)| inserted.elapsed() >= self.ttl);
+        None
+    }
+
+    pub fn insert(&self, key: &str, value: V) {
+        self.entries.insert(key.to_owned(), (Instant::now(), value));
+    }
+
+    pub fn remove(&self, key: &str) -> Option<V> {
+        self.entries.remove(key).map(|(_, (_, v))| v)
+    }
+
+    pub fn clear(&self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_reads() {
+        let cache = Arc::new(TokenCache::new());
+        for i in 0..10 {
+            cache.insert(&i.to_string(), i);
+        }
+
+        let handles = (0..50)
+            .map(|t| {
+                let cache = cache.clone();
+                tokio::spawn(async move {
+                    for n in 0..1000 {
+                        let key = ((t + n) % 10).to_string();
+                        assert_eq!(cache.get(&key), Some((t + n) % 10));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for h in handles {
+            h.await.unwrap();
+        }
+        assert_eq!(cache.get("missing"), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_misses_share_one_fetch() {
+        let cache = Arc::new(TokenCache::new());
+        let fetches = Arc::new(AtomicUsize::new(0));
+
+        let handles = (0..20)
+            .map(|_| {
+                let (cache, fetches) = (cache.clone(), fetches.clone());
+                tokio::spawn(async move {
+                    cache
+                        .get_or_fetch("1", || async {
+                            fetches.fetch_add(1, Ordering::SeqCst);
+                            tokio::task::yield_now().await;
+                            Ok(7)
+                        })
+                        .await
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for h in handles {
+            assert_eq!(h.await.unwrap().unwrap(), 7);
+        }
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.get("1"), Some(7));
+    }
+
+    #[tokio::test]
+    async fn test_failed_fetch_is_not_cached() {
+        let cache = TokenCache::<u32>::new();
+        let err = cache
+            .get_or_fetch("1", || async { Err(anyhow::anyhow!("boom")) })
+            .await;
+        assert!(err.is_err());
+        assert_eq!(cache.get("1"), None);
+
+        let v = cache.get_or_fetch("1", || async { Ok(3) }).await.unwrap();
+        assert_eq!(v, 3);
+    }
+
+    #[test]
+    fn test_ttl_cache_expiry() {
+        let cache = TtlCache::new(Duration::from_millis(20));
+        cache.insert("a", 1);
+        assert_eq!(cache.get("a"), Some(1));
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(cache.get("a"), None);
+
+        cache.insert("a", 2);
+        assert_eq!(cache.remove("a"), Some(2));
+        assert_eq!(cache.get("a"), None);
+    }
+}
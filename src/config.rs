@@ -1,5 +1,24 @@
+use alloy_primitives::Address;
+use std::str::FromStr;
+
+/// EIP-712 domain version of the exchange deployments known so far.
+pub const DEFAULT_EXCHANGE_DOMAIN_VERSION: &str = "1";
+
+/// Chains with known exchange deployments: Polygon mainnet and the Amoy testnet.
+pub const SUPPORTED_CHAINS: [u64; 2] = [137, 80002];
+
+pub fn check_chain_supported(chain_id: u64) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        SUPPORTED_CHAINS.contains(&chain_id),
+        "Unsupported chain id {chain_id}, expected one of {SUPPORTED_CHAINS:?} (Polygon, Amoy)"
+    );
+    Ok(())
+}
+
 pub struct ContractConfig {
     pub exchange: String,
+    /// EIP-712 domain version the exchange at `exchange` verifies orders with.
+    pub exchange_domain_version: String,
     pub collateral: String,
     pub conditional_tokens: String,
 }
@@ -10,12 +29,14 @@ pub fn get_contract_config(chain_id: u64, neg_risk: bool) -> Option<ContractConf
             if chain_id == 137 {
                 return Some(ContractConfig {
                     exchange: "0xC5d563A36AE78145C45a50134d48A1215220f80a".to_owned(),
+                    exchange_domain_version: DEFAULT_EXCHANGE_DOMAIN_VERSION.to_owned(),
                     collateral: "0x2791bca1f2de4661ed88a30c99a7a9449aa84174".to_owned(),
                     conditional_tokens: "0x4D97DCd97eC945f40cF65F87097ACe5EA0476045".to_owned(),
                 });
             } else if chain_id == 80002 {
                 return Some(ContractConfig {
                     exchange: "0xd91E80cF2E7be2e162c6513ceD06f1dD0dA35296".to_owned(),
+                    exchange_domain_version: DEFAULT_EXCHANGE_DOMAIN_VERSION.to_owned(),
                     collateral: "0x9c4e1703476e875070ee25b56a58b008cfb8fa78".to_owned(),
                     conditional_tokens: "0x69308FB512518e39F9b16112fA8d994F4e2Bf8bB".to_owned(),
                 });
@@ -26,12 +47,14 @@ pub fn get_contract_config(chain_id: u64, neg_risk: bool) -> Option<ContractConf
             if chain_id == 137 {
                 return Some(ContractConfig {
                     exchange: "0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E".to_owned(),
+                    exchange_domain_version: DEFAULT_EXCHANGE_DOMAIN_VERSION.to_owned(),
                     collateral: "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174".to_owned(),
                     conditional_tokens: "0x4D97DCd97eC945f40cF65F87097ACe5EA0476045".to_owned(),
                 });
             } else if chain_id == 80002 {
                 return Some(ContractConfig {
                     exchange: "0xdFE02Eb6733538f8Ea35D585af8DE5958AD99E40".to_owned(),
+                    exchange_domain_version: DEFAULT_EXCHANGE_DOMAIN_VERSION.to_owned(),
                     collateral: "0x9c4e1703476e875070ee25b56a58b008cfb8fa78".to_owned(),
                     conditional_tokens: "0x69308FB512518e39F9b16112fA8d994F4e2Bf8bB".to_owned(),
                 });
@@ -40,3 +63,14 @@ pub fn get_contract_config(chain_id: u64, neg_risk: bool) -> Option<ContractConf
         }
     }
 }
+
+/// Domain version for orders verified by `exchange` on `chain_id`. Unknown
+/// exchanges get the default version.
+pub fn exchange_domain_version(chain_id: u64, exchange: Address) -> String {
+    [false, true]
+        .into_iter()
+        .filter_map(|neg_risk| get_contract_config(chain_id, neg_risk))
+        .find(|c| Address::from_str(&c.exchange).is_ok_and(|a| a == exchange))
+        .map(|c| c.exchange_domain_version)
+        .unwrap_or_else(|| DEFAULT_EXCHANGE_DOMAIN_VERSION.to_owned())
+}
@@ -0,0 +1,296 @@
+use crate::config::get_contract_config;
+use crate::orders::USDC_DECIMALS;
+use crate::ClientResult;
+use alloy_primitives::{Address, Bytes, B256, U256};
+use alloy_sol_types::{sol, SolCall};
+use anyhow::{ensure, Context};
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+sol! {
+    interface IExchange {
+        function incrementNonce() external;
+    }
+
+    interface IERC20 {
+        function approve(address spender, uint256 amount) external returns (bool);
+    }
+
+    interface IERC1155 {
+        function setApprovalForAll(address operator, bool approved) external;
+    }
+
+    interface IConditionalTokens {
+        function splitPosition(
+            address collateralToken,
+            bytes32 parentCollectionId,
+            bytes32 conditionId,
+            uint256[] partition,
+            uint256 amount
+        ) external;
+
+        function mergePositions(
+            address collateralToken,
+            bytes32 parentCollectionId,
+            bytes32 conditionId,
+            uint256[] partition,
+            uint256 amount
+        ) external;
+    }
+}
+
+/// Index sets of the two outcomes of a binary market.
+const BINARY_PARTITION: [u8; 2] = [1, 2];
+
+/// An unsigned contract call, ready to be submitted by any Polygon provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractCall {
+    pub to: Address,
+    pub data: Bytes,
+}
+
+fn exchange_address(chain_id: u64, neg_risk: bool) -> ClientResult<Address> {
+    let config = get_contract_config(chain_id, neg_risk)
+        .context("No contract found with given chain_id and neg_risk")?;
+    Address::from_str(&config.exchange).context("Invalid exchange address")
+}
+
+/// Builds the exchange `incrementNonce()` call. Once mined, every order the
+/// sender signed with its previous nonce can no longer be filled.
+pub fn increment_nonce_call(chain_id: u64, neg_risk: bool) -> ClientResult<ContractCall> {
+    Ok(ContractCall {
+        to: exchange_address(chain_id, neg_risk)?,
+        data: IExchange::incrementNonceCall {}.abi_encode().into(),
+    })
+}
+
+/// Builds the USDC `approve` call letting the exchange move `amount` of the
+/// sender's collateral (in 6 decimal units, `U256::MAX` for unlimited).
+pub fn approve_collateral_call(
+    chain_id: u64,
+    neg_risk: bool,
+    amount: U256,
+) -> ClientResult<ContractCall> {
+    let config = get_contract_config(chain_id, neg_risk)
+        .context("No contract found with given chain_id and neg_risk")?;
+    let call = IERC20::approveCall {
+        spender: Address::from_str(&config.exchange).context("Invalid exchange address")?,
+        amount,
+    };
+    Ok(ContractCall {
+        to: Address::from_str(&config.collateral).context("Invalid collateral address")?,
+        data: call.abi_encode().into(),
+    })
+}
+
+/// Builds the conditional tokens `setApprovalForAll` call letting the exchange
+/// transfer the sender's outcome tokens, required before selling.
+pub fn approve_conditional_tokens_call(
+    chain_id: u64,
+    neg_risk: bool,
+) -> ClientResult<ContractCall> {
+    let config = get_contract_config(chain_id, neg_risk)
+        .context("No contract found with given chain_id and neg_risk")?;
+    let call = IERC1155::setApprovalForAllCall {
+        operator: Address::from_str(&config.exchange).context("Invalid exchange address")?,
+        approved: true,
+    };
+    Ok(ContractCall {
+        to: Address::from_str(&config.conditional_tokens)
+            .context("Invalid conditional tokens address")?,
+        data: call.abi_encode().into(),
+    })
+}
+
+struct CompleteSetArgs {
+    conditional_tokens: Address,
+    collateral: Address,
+    condition_id: B256,
+    partition: Vec<U256>,
+    amount: U256,
+}
+
+fn complete_set_args(
+    chain_id: u64,
+    condition_id: &str,
+    amount: Decimal,
+) -> ClientResult<CompleteSetArgs> {
+    let config =
+        get_contract_config(chain_id, false).context("No contract found with given chain_id")?;
+    ensure!(
+        amount > Decimal::ZERO,
+        "Amount must be positive, got {amount}"
+    );
+    let units = amount * Decimal::from(10u64.pow(USDC_DECIMALS));
+    ensure!(
+        units.fract().is_zero(),
+        "Amount {amount} has more than {USDC_DECIMALS} decimals"
+    );
+
+    Ok(CompleteSetArgs {
+        conditional_tokens: Address::from_str(&config.conditional_tokens)
+            .context("Invalid conditional tokens address")?,
+        collateral: Address::from_str(&config.collateral).context("Invalid collateral address")?,
+        condition_id: B256::from_str(condition_id).context("Invalid condition id")?,
+        partition: BINARY_PARTITION.map(U256::from).to_vec(),
+        amount: U256::from_str(&units.trunc().to_string()).context("Invalid amount")?,
+    })
+}
+
+/// Builds the CTF `splitPosition` call that locks `amount` USDC and mints
+/// `amount` of each outcome token of a binary market. Neg risk markets are
+/// collateralized through the neg risk adapter and are not covered.
+pub fn split_position_call(
+    chain_id: u64,
+    condition_id: &str,
+    amount: Decimal,
+) -> ClientResult<ContractCall> {
+    let args = complete_set_args(chain_id, condition_id, amount)?;
+    let call = IConditionalTokens::splitPositionCall {
+        collateralToken: args.collateral,
+        parentCollectionId: B256::ZERO,
+        conditionId: args.condition_id,
+        partition: args.partition,
+        amount: args.amount,
+    };
+    Ok(ContractCall {
+        to: args.conditional_tokens,
+        data: call.abi_encode().into(),
+    })
+}
+
+/// Builds the CTF `mergePositions` call that burns `amount` of each outcome
+/// token of a binary market and returns `amount` USDC.
+pub fn merge_positions_call(
+    chain_id: u64,
+    condition_id: &str,
+    amount: Decimal,
+) -> ClientResult<ContractCall> {
+    let args = complete_set_args(chain_id, condition_id, amount)?;
+    let call = IConditionalTokens::mergePositionsCall {
+        collateralToken: args.collateral,
+        parentCollectionId: B256::ZERO,
+        conditionId: args.condition_id,
+        partition: args.partition,
+        amount: args.amount,
+    };
+    Ok(ContractCall {
+        to: args.conditional_tokens,
+        data: call.abi_encode().into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::keccak256;
+
+    #[test]
+    fn test_increment_nonce_call() {
+        let call = increment_nonce_call(137, false).unwrap();
+        assert_eq!(
+            call.to,
+            Address::from_str("0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E").unwrap()
+        );
+        assert_eq!(call.data.as_ref(), &keccak256("incrementNonce()")[..4]);
+
+        let neg_risk = increment_nonce_call(137, true).unwrap();
+        assert_eq!(
+            neg_risk.to,
+            Address::from_str("0xC5d563A36AE78145C45a50134d48A1215220f80a").unwrap()
+        );
+        assert!(increment_nonce_call(1, false).is_err());
+    }
+
+    #[test]
+    fn test_approve_collateral_call() {
+        let call = approve_collateral_call(137, false, U256::MAX).unwrap();
+        assert_eq!(
+            call.to,
+            Address::from_str("0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174").unwrap()
+        );
+        assert_eq!(&call.data[..4], &keccak256("approve(address,uint256)")[..4]);
+        assert_eq!(
+            alloy_primitives::hex::encode(&call.data),
+            concat!(
+                "095ea7b3",
+                "0000000000000000000000004bfb41d5b3570defd03c39a9a4d8de6bd8b8982e",
+                "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            )
+        );
+
+        let neg_risk = approve_collateral_call(137, true, U256::from(5_000_000)).unwrap();
+        let decoded = IERC20::approveCall::abi_decode(&neg_risk.data, true).unwrap();
+        assert_eq!(
+            decoded.spender,
+            Address::from_str("0xC5d563A36AE78145C45a50134d48A1215220f80a").unwrap()
+        );
+        assert_eq!(decoded.amount, U256::from(5_000_000));
+    }
+
+    #[test]
+    fn test_approve_conditional_tokens_call() {
+        let call = approve_conditional_tokens_call(137, false).unwrap();
+        assert_eq!(
+            call.to,
+            Address::from_str("0x4D97DCd97eC945f40cF65F87097ACe5EA0476045").unwrap()
+        );
+        assert_eq!(
+            alloy_primitives::hex::encode(&call.data),
+            concat!(
+                "a22cb465",
+                "0000000000000000000000004bfb41d5b3570defd03c39a9a4d8de6bd8b8982e",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+            )
+        );
+        assert!(approve_conditional_tokens_call(1, false).is_err());
+    }
+
+    const CONDITION_ID: &str = "0xbd31dc8a20211944f6b70f31557f1001557b59905b7738480ca09bd4532f84af";
+
+    #[test]
+    fn test_split_position_call() {
+        let call = split_position_call(137, CONDITION_ID, Decimal::new(1_505, 1)).unwrap();
+        assert_eq!(
+            call.to,
+            Address::from_str("0x4D97DCd97eC945f40cF65F87097ACe5EA0476045").unwrap()
+        );
+        assert_eq!(
+            &call.data[..4],
+            &keccak256("splitPosition(address,bytes32,bytes32,uint256[],uint256)")[..4]
+        );
+
+        let decoded = IConditionalTokens::splitPositionCall::abi_decode(&call.data, true).unwrap();
+        assert_eq!(
+            decoded.collateralToken,
+            Address::from_str("0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174").unwrap()
+        );
+        assert_eq!(decoded.parentCollectionId, B256::ZERO);
+        assert_eq!(decoded.conditionId, B256::from_str(CONDITION_ID).unwrap());
+        assert_eq!(decoded.partition, vec![U256::from(1), U256::from(2)]);
+        assert_eq!(decoded.amount, U256::from(150_500_000u64));
+        // selector + 5 head words + partition length and two elements
+        assert_eq!(call.data.len(), 4 + 32 * 8);
+    }
+
+    #[test]
+    fn test_merge_positions_call() {
+        let call = merge_positions_call(137, CONDITION_ID, Decimal::from(10)).unwrap();
+        assert_eq!(
+            &call.data[..4],
+            &keccak256("mergePositions(address,bytes32,bytes32,uint256[],uint256)")[..4]
+        );
+        let decoded = IConditionalTokens::mergePositionsCall::abi_decode(&call.data, true).unwrap();
+        assert_eq!(decoded.amount, U256::from(10_000_000u64));
+        assert_eq!(decoded.partition, vec![U256::from(1), U256::from(2)]);
+    }
+
+    #[test]
+    fn test_complete_set_call_validation() {
+        let one = Decimal::ONE;
+        assert!(split_position_call(137, "0x1234", one).is_err());
+        assert!(split_position_call(1, CONDITION_ID, one).is_err());
+        assert!(split_position_call(137, CONDITION_ID, Decimal::ZERO).is_err());
+        assert!(merge_positions_call(137, CONDITION_ID, Decimal::new(1, 7)).is_err());
+    }
+}
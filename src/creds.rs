@@ -0,0 +1,78 @@
+use crate::{ApiCreds, ClientResult};
+use anyhow::Context;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::Path;
+
+/// Encrypts API credentials before [`crate::ClobClient::load_or_derive_api_key`]
+/// writes them to disk, and decrypts them when reading them back.
+pub trait CredsCipher: Send + Sync {
+    fn encrypt(&self, plaintext: &[u8]) -> ClientResult<Vec<u8>>;
+    fn decrypt(&self, ciphertext: &[u8]) -> ClientResult<Vec<u8>>;
+}
+
+/// Reads credentials cached at `path`. Missing, unreadable or undecodable files
+/// all count as a miss.
+pub(crate) fn load_creds(path: &Path, cipher: Option<&dyn CredsCipher>) -> Option<ApiCreds> {
+    let data = fs::read(path).ok()?;
+    let data = match cipher {
+        Some(cipher) => cipher.decrypt(&data).ok()?,
+        None => data,
+    };
+    serde_json::from_slice(&data).ok()
+}
+
+/// Writes credentials to `path`. On unix the file is readable by its owner only,
+/// since without a cipher it holds the secret in plaintext.
+pub(crate) fn store_creds(
+    path: &Path,
+    creds: &ApiCreds,
+    cipher: Option<&dyn CredsCipher>,
+) -> ClientResult<()> {
+    let data = serde_json::to_vec(creds)?;
+    let data = match cipher {
+        Some(cipher) => cipher.encrypt(&data)?,
+        None => data,
+    };
+    write_private(path, &data)
+        .with_context(|| format!("Failed to cache API credentials at {}", path.display()))
+}
+
+fn write_private(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(path)?;
+    // `mode` only applies to newly created files.
+    #[cfg(unix)]
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    file.write_all(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_store_creds_owner_only() {
+        let path = std::env::temp_dir().join(format!("creds-mode-{}.json", std::process::id()));
+        fs::write(&path, "stale").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let creds = ApiCreds {
+            api_key: "key".into(),
+            secret: "c2VjcmV0".into(),
+            passphrase: "pass".into(),
+        };
+        store_creds(&path, &creds, None).unwrap();
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(load_creds(&path, None).unwrap().api_key, "key");
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,13 +1,16 @@
 use crate::Decimal;
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
 
+use crate::utils::{get_current_unix_time_secs, parse_token_id, to_ndjson};
 use crate::SignedOrderRequest;
-use alloy_primitives::U256;
+use alloy_primitives::{Address, U256};
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
 use std::str::FromStr;
-
-const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+use std::time::Duration;
 
 pub enum AssetType {
     COLLATERAL,
@@ -93,6 +96,7 @@ impl TradeParams {
 
 #[derive(Debug, Deserialize)]
 pub struct OpenOrder {
+    #[serde(default, deserialize_with = "deserialize_null_default")]
     pub associate_trades: Vec<String>,
     pub id: String,
     pub status: String,
@@ -119,6 +123,275 @@ pub struct OpenOrder {
     pub created_at: u64,
 }
 
+#[derive(Debug, Default)]
+pub struct PositionParams {
+    pub market: Option<String>,
+    pub size_threshold: Option<Decimal>,
+    pub redeemable: Option<bool>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+impl PositionParams {
+    pub fn to_query_params(&self) -> Vec<(&str, String)> {
+        let mut params = Vec::with_capacity(5);
+
+        if let Some(x) = &self.market {
+            params.push(("market", x.clone()));
+        }
+        if let Some(x) = &self.size_threshold {
+            params.push(("sizeThreshold", x.to_string()));
+        }
+        if let Some(x) = &self.redeemable {
+            params.push(("redeemable", x.to_string()));
+        }
+        if let Some(x) = &self.limit {
+            params.push(("limit", x.to_string()));
+        }
+        if let Some(x) = &self.offset {
+            params.push(("offset", x.to_string()));
+        }
+        params
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RewardsHistoryParams {
+    /// Day to fetch earnings for, as `YYYY-MM-DD`.
+    pub date: Option<String>,
+    pub market: Option<String>,
+}
+
+impl RewardsHistoryParams {
+    pub fn to_query_params(&self) -> Vec<(&str, &String)> {
+        let mut params = Vec::with_capacity(2);
+
+        if let Some(x) = &self.date {
+            params.push(("date", x));
+        }
+        if let Some(x) = &self.market {
+            params.push(("market", x));
+        }
+        params
+    }
+}
+
+/// A reward payout for liquidity provided on one market during one epoch (day).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RewardEarning {
+    #[serde(rename = "condition_id")]
+    pub market: String,
+    #[serde(rename = "date")]
+    pub epoch: String,
+    #[serde(rename = "earnings")]
+    pub amount: Decimal,
+    #[serde(rename = "asset_address")]
+    pub token: String,
+    #[serde(default)]
+    pub maker_address: Option<String>,
+    #[serde(default)]
+    pub asset_rate: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Position {
+    pub proxy_wallet: String,
+    pub asset: String,
+    pub condition_id: String,
+    pub size: Decimal,
+    pub avg_price: Decimal,
+    pub initial_value: Decimal,
+    pub current_value: Decimal,
+    pub cash_pnl: Decimal,
+    pub realized_pnl: Decimal,
+    pub cur_price: Decimal,
+    pub redeemable: bool,
+    pub outcome: String,
+    pub outcome_index: u32,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub slug: Option<String>,
+    #[serde(default)]
+    pub opposite_asset: Option<String>,
+    #[serde(default)]
+    pub end_date: Option<String>,
+    #[serde(default)]
+    pub negative_risk: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeRole {
+    Maker,
+    Taker,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MakerOrder {
+    pub order_id: String,
+    pub maker_address: String,
+    pub owner: String,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub matched_amount: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub price: Decimal,
+    #[serde(default)]
+    pub fee_rate_bps: Option<String>,
+    pub asset_id: String,
+    pub outcome: String,
+    #[serde(default)]
+    pub side: Option<Side>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trade {
+    pub id: String,
+    pub taker_order_id: String,
+    pub market: String,
+    pub asset_id: String,
+    pub side: Side,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub size: Decimal,
+    #[serde(default)]
+    pub fee_rate_bps: Option<String>,
+    #[serde(with = "rust_decimal::serde::str")]
+    pub price: Decimal,
+    pub status: String,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub match_time: u64,
+    #[serde(default)]
+    pub last_update: Option<String>,
+    pub outcome: String,
+    pub owner: String,
+    pub maker_address: String,
+    #[serde(default)]
+    pub maker_orders: Vec<MakerOrder>,
+    #[serde(default)]
+    pub transaction_hash: Option<String>,
+    #[serde(default)]
+    pub bucket_index: Option<u64>,
+    #[serde(default)]
+    pub trader_side: Option<String>,
+}
+
+impl Trade {
+    /// Role `address` played in this trade. The top level `maker_address` belongs to
+    /// the taker order, while resting orders that were hit are listed in `maker_orders`.
+    pub fn role(&self, address: &str) -> Option<TradeRole> {
+        if self.maker_address.eq_ignore_ascii_case(address) {
+            Some(TradeRole::Taker)
+        } else if self
+            .maker_orders
+            .iter()
+            .any(|m| m.maker_address.eq_ignore_ascii_case(address))
+        {
+            Some(TradeRole::Maker)
+        } else {
+            None
+        }
+    }
+
+    /// Size and price at which `order_id` was filled in this trade, either as
+    /// the taker or as one of the resting maker orders.
+    pub fn fill_for_order(&self, order_id: &str) -> Option<(Decimal, Decimal)> {
+        if self.taker_order_id.eq_ignore_ascii_case(order_id) {
+            return Some((self.size, self.price));
+        }
+        self.maker_orders
+            .iter()
+            .find(|m| m.order_id.eq_ignore_ascii_case(order_id))
+            .map(|m| (m.matched_amount, m.price))
+    }
+}
+
+/// How much of an order has filled, combining the order's own `size_matched`
+/// with the prices of the trades that filled it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FillReport {
+    pub order_id: String,
+    pub original_size: Decimal,
+    /// `size_matched` as reported by the order.
+    pub filled_size: Decimal,
+    /// Size weighted price over the order's trades, `None` before any fill.
+    pub avg_fill_price: Option<Decimal>,
+    pub remaining: Decimal,
+}
+
+impl FillReport {
+    /// Failed trades are left out of the average.
+    pub fn new(order: &OpenOrder, trades: &[Trade]) -> Self {
+        let (size, notional) = trades
+            .iter()
+            .filter(|t| !t.status.eq_ignore_ascii_case("FAILED"))
+            .filter_map(|t| t.fill_for_order(&order.id))
+            .fold(
+                (Decimal::ZERO, Decimal::ZERO),
+                |(size, notional), (s, p)| (size + s, notional + s * p),
+            );
+        FillReport {
+            order_id: order.id.clone(),
+            original_size: order.original_size,
+            filled_size: order.size_matched,
+            avg_fill_price: (!size.is_zero()).then(|| (notional / size).normalize()),
+            remaining: (order.original_size - order.size_matched).max(Decimal::ZERO),
+        }
+    }
+}
+
+/// An entry of the market live activity feed. Only the identifying fields are
+/// typed, everything else is kept as returned by the server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketTradeEvent {
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub transaction_hash: Option<String>,
+    #[serde(flatten)]
+    pub data: HashMap<String, Value>,
+}
+
+impl MarketTradeEvent {
+    /// Key used to tell events apart between polls.
+    pub fn dedup_key(&self) -> String {
+        match (&self.id, &self.transaction_hash) {
+            (Some(id), _) => id.clone(),
+            (None, Some(hash)) => hash.clone(),
+            (None, None) => serde_json::to_string(&self.data).unwrap_or_default(),
+        }
+    }
+}
+
+/// Splits a poll of the activity feed into events not present in the previous
+/// poll, and the keys to compare the next poll against.
+pub(crate) fn new_trade_events(
+    seen: &HashSet<String>,
+    events: Vec<MarketTradeEvent>,
+) -> (Vec<MarketTradeEvent>, HashSet<String>) {
+    let keys = events.iter().map(MarketTradeEvent::dedup_key).collect();
+    let fresh = events
+        .into_iter()
+        .filter(|e| !seen.contains(&e.dedup_key()))
+        .collect();
+    (fresh, keys)
+}
+
+/// Outcome of a cancel request. Orders that could not be canceled, e.g. because
+/// they were already filled, are listed with the reason given by the server.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CancelResponse {
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub canceled: Vec<String>,
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub not_canceled: HashMap<String, String>,
+}
+
+impl CancelResponse {
+    pub fn all_canceled(&self) -> bool {
+        self.not_canceled.is_empty()
+    }
+}
+
 #[derive(Debug)]
 pub struct OpenOrderParams {
     pub id: Option<String>,
@@ -164,6 +437,44 @@ where
     }
 }
 
+fn deserialize_null_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+fn deserialize_optional_decimal<'de, D>(deserializer: D) -> Result<Option<Decimal>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<Value>::deserialize(deserializer)? {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(s)) if s.trim().is_empty() => Ok(None),
+        Some(Value::String(s)) => parse_decimal(s.trim())
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+        Some(Value::Number(n)) => parse_decimal(&n.to_string())
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+        Some(v) => Err(serde::de::Error::custom(format!(
+            "expected a decimal string or number, got {v}"
+        ))),
+    }
+}
+
+fn deserialize_nonzero_decimal<'de, D>(deserializer: D) -> Result<Option<Decimal>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(deserialize_optional_decimal(deserializer)?.filter(|d| !d.is_zero()))
+}
+
+fn parse_decimal(s: &str) -> Result<Decimal, rust_decimal::Error> {
+    Decimal::from_str(s).or_else(|_| Decimal::from_scientific(s))
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PostOrder {
@@ -182,32 +493,101 @@ impl PostOrder {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct OrderArgs {
     pub token_id: String,
+    #[serde(with = "rust_decimal::serde::str")]
     pub price: Decimal,
+    #[serde(with = "rust_decimal::serde::str")]
     pub size: Decimal,
     pub side: Side,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct OrderBookSummary {
     pub market: String,
     pub asset_id: String,
     pub hash: String,
     #[serde(deserialize_with = "deserialize_number_from_string")]
     pub timestamp: u64,
+    /// Empty when the server sends `null`, as it does for brand new markets.
+    #[serde(default, deserialize_with = "deserialize_null_default")]
     pub bids: Vec<OrderSummary>,
+    #[serde(default, deserialize_with = "deserialize_null_default")]
     pub asks: Vec<OrderSummary>,
+    #[serde(default, deserialize_with = "deserialize_optional_decimal")]
+    pub min_order_size: Option<Decimal>,
+    #[serde(default, deserialize_with = "deserialize_optional_decimal")]
+    pub tick_size: Option<Decimal>,
+    #[serde(default)]
+    pub neg_risk: Option<bool>,
 }
 
-#[derive(Debug)]
+/// Everything needed to validate an order for a token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderConstraints {
+    pub min_tick_size: Decimal,
+    pub min_order_size: Decimal,
+    pub neg_risk: bool,
+}
+
+/// Midpoints of both outcomes of a binary market, matched to Yes/No by outcome
+/// name (see [`Market::yes_no_tokens`]). For outcomes not named Yes/No, `yes` is
+/// the first token listed on the market and `no` the second.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BinaryMarketPrices {
+    pub yes: Option<Decimal>,
+    pub no: Option<Decimal>,
+    /// `yes + no`, when both are known. Far from 1 means a mispricing.
+    pub sum: Option<Decimal>,
+}
+
+impl BinaryMarketPrices {
+    pub fn new(yes: Option<Decimal>, no: Option<Decimal>) -> Self {
+        BinaryMarketPrices {
+            yes,
+            no,
+            sum: yes.zip(no).map(|(y, n)| y + n),
+        }
+    }
+}
+
+/// Best bid and ask of a token's book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TopOfBook {
+    pub best_bid: Option<OrderSummary>,
+    pub best_ask: Option<OrderSummary>,
+}
+
+impl OrderBookSummary {
+    pub fn best_bid(&self) -> Option<&OrderSummary> {
+        self.bids.iter().max_by_key(|o| o.price)
+    }
+
+    pub fn best_ask(&self) -> Option<&OrderSummary> {
+        self.asks.iter().min_by_key(|o| o.price)
+    }
+
+    pub fn midpoint(&self) -> Option<Decimal> {
+        Some((self.best_bid()?.price + self.best_ask()?.price) / Decimal::TWO)
+    }
+
+    pub fn top_of_book(&self) -> TopOfBook {
+        TopOfBook {
+            best_bid: self.best_bid().copied(),
+            best_ask: self.best_ask().copied(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct MarketOrderArgs {
     pub token_id: String,
+    #[serde(with = "rust_decimal::serde::str")]
     pub amount: Decimal,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
 pub struct OrderSummary {
     #[serde(with = "rust_decimal::serde::str")]
     pub price: Decimal,
@@ -224,29 +604,257 @@ impl OrderArgs {
             side,
         }
     }
+
+    /// Like [`OrderArgs::new`] but rejects a malformed token id up front.
+    pub fn try_new(
+        token_id: &str,
+        price: Decimal,
+        size: Decimal,
+        side: Side,
+    ) -> crate::ClientResult<Self> {
+        parse_token_id(token_id)?;
+        Ok(Self::new(token_id, price, size, side))
+    }
+
+    /// Args for spending (or receiving) `notional` USDC at `price`, i.e. a size of
+    /// `notional / price` shares. The size is rounded like any other when signed.
+    pub fn from_notional(
+        token_id: &str,
+        price: Decimal,
+        notional: Decimal,
+        side: Side,
+    ) -> crate::ClientResult<Self> {
+        if price <= Decimal::ZERO {
+            return Err(anyhow::anyhow!("Price must be positive, got {price}"));
+        }
+        Ok(Self::new(token_id, price, notional / price, side))
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ExtraOrderArgs {
-    pub fee_rate_bps: u32,
+    /// Fee rate to sign. `None` lets [`crate::ClobClient`] look up the token's
+    /// fee rate; offline signing uses 0.
+    #[serde(default)]
+    pub fee_rate_bps: Option<u32>,
     pub nonce: U256,
-    pub taker: String,
+    /// Restricts who may fill the order. `None` signs the zero address, which
+    /// makes the order public: anyone can fill it.
+    #[serde(default)]
+    pub taker: Option<Address>,
+    /// Fixed salt instead of a random one. Signing the same order twice with the
+    /// same salt yields the same order hash, which is the order id, so the CLOB
+    /// rejects the second post as a duplicate. This makes retries idempotent; the
+    /// salt must then differ between orders that are meant to be distinct.
+    #[serde(default)]
+    pub salt: Option<u64>,
+}
+
+impl ExtraOrderArgs {
+    pub fn with_fee_rate_bps(fee_rate_bps: u32) -> Self {
+        ExtraOrderArgs {
+            fee_rate_bps: Some(fee_rate_bps),
+            ..Default::default()
+        }
+    }
+
+    /// Args for a private (e.g. RFQ) order only `taker` can fill.
+    pub fn private_to(taker: Address) -> crate::ClientResult<Self> {
+        if taker.is_zero() {
+            return Err(anyhow::anyhow!(
+                "Private orders need a non-zero taker, the zero address makes the order public"
+            ));
+        }
+        Ok(ExtraOrderArgs {
+            taker: Some(taker),
+            ..Default::default()
+        })
+    }
+
+    /// Sets the taker from a hex string, rejecting malformed addresses immediately.
+    pub fn with_taker(mut self, taker: &str) -> crate::ClientResult<Self> {
+        let taker = Address::from_str(taker)
+            .map_err(|e| anyhow::anyhow!("Invalid taker address {taker:?}: {e}"))?;
+        self.taker = Some(taker);
+        Ok(self)
+    }
+
+    pub fn with_salt(mut self, salt: u64) -> Self {
+        self.salt = Some(salt);
+        self
+    }
+
+    pub fn taker_address(&self) -> Address {
+        self.taker.unwrap_or(Address::ZERO)
+    }
 }
 
 impl Default for ExtraOrderArgs {
     fn default() -> Self {
         ExtraOrderArgs {
-            fee_rate_bps: 0,
+            fee_rate_bps: None,
             nonce: U256::ZERO,
-            taker: ZERO_ADDRESS.into(),
+            taker: None,
+            salt: None,
         }
     }
 }
 
-#[derive(Debug, Default)]
+/// Expiration of a GTD order.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Expiration {
+    /// No expiration (required for all order types except GTD).
+    #[default]
+    None,
+    /// Absolute unix timestamp in seconds.
+    At(u64),
+    /// Relative to the time the order is created.
+    In(Duration),
+}
+
+impl Expiration {
+    /// Unix timestamp in seconds to put on the order, `0` meaning no expiration.
+    pub fn to_timestamp(&self) -> u64 {
+        match self {
+            Expiration::None => 0,
+            Expiration::At(ts) => *ts,
+            Expiration::In(d) => get_current_unix_time_secs() + d.as_secs(),
+        }
+    }
+}
+
+impl From<u64> for Expiration {
+    fn from(ts: u64) -> Self {
+        Expiration::At(ts)
+    }
+}
+
+impl From<Duration> for Expiration {
+    fn from(d: Duration) -> Self {
+        Expiration::In(d)
+    }
+}
+
+/// How a limit price with more decimals than the tick size allows is rounded.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PriceRounding {
+    /// Round to the nearest tick, halfway prices towards zero.
+    #[default]
+    Nearest,
+    /// Round in the caller's favour: bids down and asks up, so the order never
+    /// pays more or receives less than the requested price.
+    Conservative,
+}
+
+/// Per order options. Build them from `CreateOrderOptions::default()` with the
+/// `with_*` methods; new options may be added in any release.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct CreateOrderOptions {
+    #[serde(default, with = "rust_decimal::serde::str_option")]
     pub tick_size: Option<Decimal>,
     pub neg_risk: Option<bool>,
+    /// Market orders only: reject if filling would walk more than this many book levels.
+    pub max_book_levels: Option<usize>,
+    /// Market orders only: reject if the fill price is further than this from the midpoint.
+    pub max_slippage_bps: Option<u32>,
+    /// Market orders only: reject if the fetched book is older than this many
+    /// milliseconds, judged by its `timestamp`.
+    pub max_book_age_ms: Option<u64>,
+    /// Limit orders only: reject if rounding the size to the tick size's share
+    /// precision (`RoundConfig::size`) drops more than this fraction of it,
+    /// e.g. `0.01` for 1%. Sizes that round to zero are always rejected.
+    #[serde(default, with = "rust_decimal::serde::str_option")]
+    pub max_size_rounding: Option<Decimal>,
+    /// Limit orders only: reject sizes that round below the market's minimum
+    /// order size. When unset but `max_size_rounding` is given, the client
+    /// fetches it with [`crate::ClobClient::get_order_constraints`].
+    #[serde(default, with = "rust_decimal::serde::str_option")]
+    pub min_order_size: Option<Decimal>,
+    /// Limit orders only: look up the token's market and reject the order if
+    /// its `accepting_order_timestamp` is still in the future. Costs two extra
+    /// requests unless the market is cached. Needs the `chrono` feature.
+    #[serde(default)]
+    pub check_accepting_order_time: bool,
+    /// Limit orders only: skip the client side check that the price lies within
+    /// one tick of 0 and 1, leaving validation to the server.
+    #[serde(default)]
+    pub skip_price_validation: bool,
+    /// Limit orders only: fetch the book and reject the order if its price is
+    /// more than this many bps through the best opposite quote, e.g. a bid
+    /// priced well above the best ask. Off by default.
+    #[serde(default)]
+    pub max_price_through_book_bps: Option<u32>,
+    /// Limit orders only: rounding applied to a price that is not on the tick
+    /// grid.
+    #[serde(default)]
+    pub price_rounding: PriceRounding,
+}
+
+impl CreateOrderOptions {
+    pub fn with_tick_size(mut self, tick_size: Decimal) -> Self {
+        self.tick_size = Some(tick_size);
+        self
+    }
+
+    pub fn with_neg_risk(mut self, neg_risk: bool) -> Self {
+        self.neg_risk = Some(neg_risk);
+        self
+    }
+
+    pub fn with_max_book_levels(mut self, max_book_levels: usize) -> Self {
+        self.max_book_levels = Some(max_book_levels);
+        self
+    }
+
+    pub fn with_max_slippage_bps(mut self, max_slippage_bps: u32) -> Self {
+        self.max_slippage_bps = Some(max_slippage_bps);
+        self
+    }
+
+    pub fn with_max_book_age_ms(mut self, max_book_age_ms: u64) -> Self {
+        self.max_book_age_ms = Some(max_book_age_ms);
+        self
+    }
+
+    pub fn with_max_size_rounding(mut self, max_size_rounding: Decimal) -> Self {
+        self.max_size_rounding = Some(max_size_rounding);
+        self
+    }
+
+    pub fn with_min_order_size(mut self, min_order_size: Decimal) -> Self {
+        self.min_order_size = Some(min_order_size);
+        self
+    }
+
+    pub fn with_check_accepting_order_time(mut self, check_accepting_order_time: bool) -> Self {
+        self.check_accepting_order_time = check_accepting_order_time;
+        self
+    }
+
+    pub fn with_skip_price_validation(mut self, skip_price_validation: bool) -> Self {
+        self.skip_price_validation = skip_price_validation;
+        self
+    }
+
+    pub fn with_max_price_through_book_bps(mut self, max_price_through_book_bps: u32) -> Self {
+        self.max_price_through_book_bps = Some(max_price_through_book_bps);
+        self
+    }
+
+    pub fn with_price_rounding(mut self, price_rounding: PriceRounding) -> Self {
+        self.price_rounding = price_rounding;
+        self
+    }
+}
+
+/// How an auto paginating call walked its pages.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PaginationStats {
+    pub pages: usize,
+    pub total_items: usize,
+    /// `next_cursor` reported by the last page fetched, e.g. `LTE=` at the end.
+    pub last_cursor: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -257,14 +865,17 @@ pub struct ApiKeysResponse {
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct MidpointResponse {
-    #[serde(with = "rust_decimal::serde::str")]
-    pub mid: Decimal,
+    /// `None` when the book is empty and the server has no midpoint to report.
+    #[serde(default, deserialize_with = "deserialize_nonzero_decimal")]
+    pub mid: Option<Decimal>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct PriceResponse {
-    #[serde(with = "rust_decimal::serde::str")]
-    pub price: Decimal,
+    /// `None` when the book has no liquidity on the requested side and the
+    /// server answers with an empty or zero price.
+    #[serde(default, deserialize_with = "deserialize_nonzero_decimal")]
+    pub price: Option<Decimal>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -278,6 +889,12 @@ pub struct TickSizeResponse {
     pub minimum_tick_size: Decimal,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct FeeRateResponse {
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub base_fee: u32,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct NegRiskResponse {
     pub neg_risk: bool,
@@ -315,6 +932,28 @@ impl Side {
             Side::SELL => "SELL",
         }
     }
+
+    pub fn opposite(&self) -> Side {
+        match self {
+            Side::BUY => Side::SELL,
+            Side::SELL => Side::BUY,
+        }
+    }
+}
+
+impl FromStr for Side {
+    type Err = anyhow::Error;
+
+    /// Accepts `BUY`/`SELL` in any case, or the numeric `0`/`1` used on chain.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "0" => Ok(Side::BUY),
+            "1" => Ok(Side::SELL),
+            s if s.eq_ignore_ascii_case("BUY") => Ok(Side::BUY),
+            s if s.eq_ignore_ascii_case("SELL") => Ok(Side::SELL),
+            s => Err(anyhow::anyhow!("Invalid side {s}")),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -323,14 +962,33 @@ pub struct BookParams {
     pub side: Side,
 }
 
-#[derive(Default, Debug, Serialize, Deserialize)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct ApiCreds {
-    #[serde(rename = "apiKey")]
+    #[serde(rename = "apiKey", alias = "api_key", alias = "key")]
     pub api_key: String,
+    #[serde(alias = "api_secret", alias = "apiSecret")]
     pub secret: String,
+    #[serde(alias = "api_passphrase", alias = "apiPassphrase")]
     pub passphrase: String,
 }
 
+impl ApiCreds {
+    /// Fails if any field is blank, which the server would only report as a 401.
+    pub fn validate(&self) -> crate::ClientResult<()> {
+        for (name, value) in [
+            ("api_key", &self.api_key),
+            ("secret", &self.secret),
+            ("passphrase", &self.passphrase),
+        ] {
+            anyhow::ensure!(
+                !value.trim().is_empty(),
+                "API credentials have an empty {name}"
+            );
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MarketsResponse {
     pub limit: Decimal,
@@ -339,6 +997,13 @@ pub struct MarketsResponse {
     pub data: Vec<Market>,
 }
 
+impl MarketsResponse {
+    /// `data` as newline delimited JSON, one market per line.
+    pub fn to_ndjson(&self) -> crate::ClientResult<String> {
+        to_ndjson(&self.data)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SimplifiedMarketsResponse {
     pub limit: Decimal,
@@ -347,7 +1012,7 @@ pub struct SimplifiedMarketsResponse {
     pub data: Vec<SimplifiedMarket>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Market {
     pub condition_id: String,
     pub tokens: [Token; 2],
@@ -369,9 +1034,158 @@ pub struct Market {
     pub seconds_delay: Decimal,
     pub icon: String,
     pub fpmm: String,
+    #[serde(default)]
+    pub accepting_orders: Option<bool>,
+    /// When the market starts accepting orders, as an ISO 8601 timestamp.
+    #[serde(default)]
+    pub accepting_order_timestamp: Option<String>,
+    #[serde(default)]
+    pub maker_base_fee: Option<u32>,
+    #[serde(default)]
+    pub taker_base_fee: Option<u32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Whether a market can be traded right now, and if not, why.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarketStatus {
+    Tradeable,
+    Inactive,
+    Closed,
+    NotAcceptingOrders,
+}
+
+impl MarketStatus {
+    pub fn is_tradeable(&self) -> bool {
+        *self == MarketStatus::Tradeable
+    }
+}
+
+impl Display for MarketStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match self {
+            MarketStatus::Tradeable => "market is accepting orders",
+            MarketStatus::Inactive => "market is not active",
+            MarketStatus::Closed => "market is closed",
+            MarketStatus::NotAcceptingOrders => "market is not accepting orders",
+        };
+        f.write_str(reason)
+    }
+}
+
+impl Market {
+    pub fn status(&self) -> MarketStatus {
+        if self.closed {
+            MarketStatus::Closed
+        } else if !self.active {
+            MarketStatus::Inactive
+        } else if self.accepting_orders == Some(false) {
+            MarketStatus::NotAcceptingOrders
+        } else {
+            MarketStatus::Tradeable
+        }
+    }
+
+    pub fn is_tradeable(&self) -> bool {
+        self.status().is_tradeable()
+    }
+
+    /// Parsed `end_date_iso`. Date only values are taken as midnight UTC.
+    #[cfg(feature = "chrono")]
+    pub fn ends_at(&self) -> Option<DateTime<Utc>> {
+        parse_market_time(self.end_date_iso.as_deref()?)
+    }
+
+    /// Parsed `accepting_order_timestamp`.
+    #[cfg(feature = "chrono")]
+    pub fn accepting_orders_from(&self) -> Option<DateTime<Utc>> {
+        parse_market_time(self.accepting_order_timestamp.as_deref()?)
+    }
+
+    /// Parsed `game_start_time`, for sports markets.
+    #[cfg(feature = "chrono")]
+    pub fn game_start_at(&self) -> Option<DateTime<Utc>> {
+        parse_market_time(self.game_start_time.as_deref()?)
+    }
+
+    /// Whether the game starts within `[from, to)`. `false` without a parseable
+    /// start time.
+    #[cfg(feature = "chrono")]
+    pub fn starts_between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> bool {
+        self.game_start_at()
+            .is_some_and(|start| from <= start && start < to)
+    }
+
+    /// The other outcome's token id, or `None` if `token_id` is not in this market.
+    pub fn complement(&self, token_id: &str) -> Option<&str> {
+        match &self.tokens {
+            [a, b] if a.token_id == token_id => Some(&b.token_id),
+            [a, b] if b.token_id == token_id => Some(&a.token_id),
+            _ => None,
+        }
+    }
+
+    /// The Yes and No tokens, matched by outcome name. Markets whose outcomes
+    /// aren't named Yes/No get their tokens in listed order.
+    pub fn yes_no_tokens(&self) -> (&Token, &Token) {
+        let [a, b] = &self.tokens;
+        let is_yes = |t: &Token| t.outcome.eq_ignore_ascii_case("yes");
+        let is_no = |t: &Token| t.outcome.eq_ignore_ascii_case("no");
+        if is_no(a) && is_yes(b) {
+            (b, a)
+        } else {
+            (a, b)
+        }
+    }
+}
+
+/// Market timestamps come as RFC 3339, as `2024-11-10 01:00:00+00` (game start
+/// times) or as plain dates, which are taken as midnight UTC.
+#[cfg(feature = "chrono")]
+fn parse_market_time(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.trim();
+    DateTime::parse_from_rfc3339(s)
+        .or_else(|_| DateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%#z"))
+        .map(|d| d.with_timezone(&Utc))
+        .or_else(|_| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d").map(|d| d.and_time(NaiveTime::MIN).and_utc())
+        })
+        .ok()
+}
+
+/// Markets ending within `[from, to)`. Markets without a parseable end date are dropped.
+#[cfg(feature = "chrono")]
+pub fn markets_ending_between(
+    markets: Vec<Market>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Vec<Market> {
+    markets
+        .into_iter()
+        .filter(|m| m.ends_at().is_some_and(|end| from <= end && end < to))
+        .collect()
+}
+
+/// Markets whose game starts within `[from, to)`. Markets without a parseable
+/// start time are dropped.
+#[cfg(feature = "chrono")]
+pub fn markets_starting_between(
+    markets: Vec<Market>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Vec<Market> {
+    markets
+        .into_iter()
+        .filter(|m| m.starts_between(from, to))
+        .collect()
+}
+
+/// Sorts markets so the ones expiring soonest come first, undated ones last.
+#[cfg(feature = "chrono")]
+pub fn sort_markets_by_end_date(markets: &mut [Market]) {
+    markets.sort_by_key(|m| (m.ends_at().is_none(), m.ends_at()));
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SimplifiedMarket {
     pub condition_id: String,
     pub tokens: [Token; 2],
@@ -382,19 +1196,901 @@ pub struct SimplifiedMarket {
     pub closed: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Token {
     pub token_id: String,
     pub outcome: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Rewards {
     pub rates: Option<Value>,
     pub min_size: Decimal,
     pub max_spread: Decimal,
     pub event_start_date: Option<String>,
     pub event_end_date: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_decimal")]
     pub in_game_multiplier: Option<Decimal>,
+    #[serde(default, deserialize_with = "deserialize_optional_decimal")]
     pub reward_epoch: Option<Decimal>,
 }
+
+impl Rewards {
+    /// Whether the market pays liquidity rewards at all.
+    pub fn is_eligible(&self) -> bool {
+        self.min_size > Decimal::ZERO
+            || self
+                .rates
+                .as_ref()
+                .is_some_and(|r| r.as_array().is_none_or(|a| !a.is_empty()))
+    }
+
+    /// Sum of `rewards_daily_rate` over all reward assets, if the market has any rates.
+    pub fn daily_rate(&self) -> Option<Decimal> {
+        let rates = self.rates.as_ref()?.as_array()?;
+        rates
+            .iter()
+            .filter_map(|r| match &r["rewards_daily_rate"] {
+                Value::Number(n) => parse_decimal(&n.to_string()).ok(),
+                Value::String(s) => parse_decimal(s).ok(),
+                _ => None,
+            })
+            .reduce(|a, b| a + b)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RewardStatus {
+    pub order_id: String,
+    pub market: String,
+    pub scoring: bool,
+    pub daily_rate: Option<Decimal>,
+}
+
+/// Factor by which one sided liquidity is discounted in the reward formula.
+const SINGLE_SIDED_REWARD_DIVISOR: Decimal = Decimal::from_parts(3, 0, 0, false, 0);
+
+/// Approximate liquidity reward score of `quotes` resting on `book`, following
+/// the published scoring: a quote at least `min_size` shares large and within
+/// `max_spread` cents of the midpoint scores `((max_spread - spread) / max_spread)^2 * size`.
+/// Bids and asks are summed separately and combined so two sided quoting scores
+/// best; when the midpoint is outside `[0.10, 0.90]` only two sided quotes count.
+/// Only meaningful relative to other quote sets, and `None` without a midpoint.
+pub fn estimate_reward_score(
+    book: &OrderBookSummary,
+    quotes: &[OrderArgs],
+    rewards: &Rewards,
+) -> Option<Decimal> {
+    let midpoint = book.midpoint()?;
+    let max_spread = rewards.max_spread;
+    let (mut bids, mut asks) = (Decimal::ZERO, Decimal::ZERO);
+
+    for quote in quotes {
+        let spread = (quote.price - midpoint).abs() * Decimal::ONE_HUNDRED;
+        if quote.size < rewards.min_size || spread >= max_spread {
+            continue;
+        }
+        let closeness = (max_spread - spread) / max_spread;
+        let score = closeness * closeness * quote.size;
+        match quote.side {
+            Side::BUY => bids += score,
+            Side::SELL => asks += score,
+        }
+    }
+
+    let two_sided = bids.min(asks);
+    if (Decimal::new(10, 2)..=Decimal::new(90, 2)).contains(&midpoint) {
+        Some(two_sided.max(bids.max(asks) / SINGLE_SIDED_REWARD_DIVISOR))
+    } else {
+        Some(two_sided)
+    }
+}
+
+pub(crate) fn collect_reward_statuses(
+    orders: &[OpenOrder],
+    scoring: &HashMap<String, bool>,
+    rewards: &HashMap<String, Rewards>,
+) -> Vec<RewardStatus> {
+    orders
+        .iter()
+        .map(|o| RewardStatus {
+            order_id: o.id.clone(),
+            market: o.market.clone(),
+            scoring: scoring.get(&o.id).copied().unwrap_or(false),
+            daily_rate: rewards.get(&o.market).and_then(Rewards::daily_rate),
+        })
+        .collect()
+}
+
+pub(crate) fn group_orders_by_market(orders: Vec<OpenOrder>) -> HashMap<String, Vec<OpenOrder>> {
+    let mut grouped: HashMap<String, Vec<OpenOrder>> = HashMap::new();
+    for o in orders {
+        grouped.entry(o.market.clone()).or_default().push(o);
+    }
+    grouped
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    #[test]
+    fn test_midpoint_response() {
+        let resp: MidpointResponse = serde_json::from_str(r#"{"mid": "0.455"}"#).unwrap();
+        assert_eq!(resp.mid, Some(Decimal::from_str("0.455").unwrap()));
+
+        for body in [
+            r#"{"mid": ""}"#,
+            r#"{"mid": null}"#,
+            r#"{"mid": "0"}"#,
+            "{}",
+        ] {
+            let resp: MidpointResponse = serde_json::from_str(body).unwrap();
+            assert_eq!(resp.mid, None, "{body}");
+        }
+    }
+
+    #[test]
+    fn test_price_response_without_liquidity() {
+        let empty: PriceResponse = serde_json::from_str(r#"{"price": ""}"#).unwrap();
+        assert_eq!(empty.price, None);
+
+        let zero: PriceResponse = serde_json::from_str(r#"{"price": "0"}"#).unwrap();
+        assert_eq!(zero.price, None);
+
+        let missing: PriceResponse = serde_json::from_str("{}").unwrap();
+        assert_eq!(missing.price, None);
+    }
+
+    fn open_order_json(id: &str, market: &str) -> Value {
+        serde_json::json!({
+            "associate_trades": [],
+            "id": id,
+            "status": "LIVE",
+            "market": market,
+            "original_size": "10",
+            "outcome": "Yes",
+            "maker_address": "0x0000000000000000000000000000000000000001",
+            "owner": "owner",
+            "price": "0.5",
+            "side": "BUY",
+            "size_matched": "0",
+            "asset_id": "1",
+            "expiration": "0",
+            "type": "GTC",
+            "created_at": 1700000000
+        })
+    }
+
+    fn open_order(id: &str, market: &str) -> OpenOrder {
+        serde_json::from_value(open_order_json(id, market)).unwrap()
+    }
+
+    #[test]
+    fn test_open_order_null_associate_trades() {
+        let mut json = open_order_json("a", "m");
+        json["associate_trades"] = Value::Null;
+        let order: OpenOrder = serde_json::from_value(json.clone()).unwrap();
+        assert!(order.associate_trades.is_empty());
+
+        json.as_object_mut().unwrap().remove("associate_trades");
+        let order: OpenOrder = serde_json::from_value(json).unwrap();
+        assert!(order.associate_trades.is_empty());
+    }
+
+    #[test]
+    fn test_new_trade_events() {
+        let event = |id: &str| -> MarketTradeEvent {
+            serde_json::from_value(serde_json::json!({"id": id, "price": "0.5"})).unwrap()
+        };
+
+        let (fresh, seen) = new_trade_events(&HashSet::new(), vec![event("a")]);
+        assert_eq!(fresh, vec![event("a")]);
+
+        let (fresh, seen) = new_trade_events(&seen, vec![event("b"), event("a")]);
+        assert_eq!(fresh, vec![event("b")]);
+        assert_eq!(seen.len(), 2);
+        assert_eq!(event("a").data["price"], "0.5");
+    }
+
+    #[test]
+    fn test_cancel_response() {
+        let resp: CancelResponse = serde_json::from_str(
+            r#"{
+                "canceled": ["0xaaa", "0xbbb"],
+                "not_canceled": {
+                    "0xccc": "order can't be found - already canceled or matched",
+                    "0xddd": "the order is already canceled"
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(resp.canceled, ["0xaaa", "0xbbb"]);
+        assert_eq!(resp.not_canceled.len(), 2);
+        assert_eq!(resp.not_canceled["0xddd"], "the order is already canceled");
+        assert!(!resp.all_canceled());
+
+        let resp: CancelResponse =
+            serde_json::from_str(r#"{"canceled": ["0xaaa"], "not_canceled": null}"#).unwrap();
+        assert!(resp.all_canceled());
+        let resp: CancelResponse = serde_json::from_str(r#"{"canceled": null}"#).unwrap();
+        assert_eq!(resp, CancelResponse::default());
+    }
+
+    #[test]
+    fn test_reward_earnings_deserialize() {
+        let page = serde_json::json!({
+            "limit": 100,
+            "count": 2,
+            "next_cursor": "LTE=",
+            "data": [
+                {
+                    "date": "2024-11-20",
+                    "condition_id": "0xcond1",
+                    "asset_address": "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174",
+                    "maker_address": "0x0000000000000000000000000000000000000001",
+                    "earnings": 1.254932,
+                    "asset_rate": 1
+                },
+                {
+                    "date": "2024-11-20",
+                    "condition_id": "0xcond2",
+                    "asset_address": "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174",
+                    "earnings": "0.5"
+                }
+            ]
+        });
+        let earnings: Vec<RewardEarning> = serde_json::from_value(page["data"].clone()).unwrap();
+
+        assert_eq!(earnings.len(), 2);
+        assert_eq!(earnings[0].market, "0xcond1");
+        assert_eq!(earnings[0].epoch, "2024-11-20");
+        assert_eq!(earnings[0].amount, Decimal::from_str("1.254932").unwrap());
+        assert_eq!(earnings[0].asset_rate, Some(Decimal::ONE));
+        assert_eq!(earnings[1].amount, Decimal::from_str("0.5").unwrap());
+        assert_eq!(earnings[1].maker_address, None);
+
+        let params = RewardsHistoryParams {
+            date: Some("2024-11-20".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            params.to_query_params(),
+            [("date", &"2024-11-20".to_owned())]
+        );
+    }
+
+    #[test]
+    fn test_api_creds_aliases() {
+        let camel: ApiCreds =
+            serde_json::from_str(r#"{"apiKey": "k", "secret": "s", "passphrase": "p"}"#).unwrap();
+        let snake: ApiCreds =
+            serde_json::from_str(r#"{"api_key": "k", "api_secret": "s", "api_passphrase": "p"}"#)
+                .unwrap();
+        for creds in [&camel, &snake] {
+            assert_eq!(
+                (
+                    creds.api_key.as_str(),
+                    creds.secret.as_str(),
+                    creds.passphrase.as_str()
+                ),
+                ("k", "s", "p")
+            );
+            assert!(creds.validate().is_ok());
+        }
+        assert_eq!(serde_json::to_value(&snake).unwrap()["apiKey"], "k");
+
+        let blank = ApiCreds {
+            passphrase: " ".into(),
+            ..camel
+        };
+        let err = blank.validate().unwrap_err();
+        assert!(err.to_string().contains("empty passphrase"), "{err}");
+    }
+
+    #[test]
+    fn test_group_orders_by_market() {
+        let orders = vec![
+            open_order("0x01", "m1"),
+            open_order("0x02", "m2"),
+            open_order("0x03", "m1"),
+        ];
+        let grouped = group_orders_by_market(orders);
+
+        assert_eq!(grouped.len(), 2);
+        let ids = |m: &str| grouped[m].iter().map(|o| o.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids("m1"), ["0x01", "0x03"]);
+        assert_eq!(ids("m2"), ["0x02"]);
+    }
+
+    #[test]
+    fn test_collect_reward_statuses() {
+        let orders = vec![open_order("0x01", "m1"), open_order("0x02", "m2")];
+        let scoring = HashMap::from([("0x01".to_owned(), true), ("0x02".to_owned(), false)]);
+        let rewards: Rewards = serde_json::from_value(serde_json::json!({
+            "rates": [{"asset_address": "0x2791", "rewards_daily_rate": 25}],
+            "min_size": 50,
+            "max_spread": 3.5
+        }))
+        .unwrap();
+        let rewards = HashMap::from([("m1".to_owned(), rewards)]);
+
+        let statuses = collect_reward_statuses(&orders, &scoring, &rewards);
+
+        assert_eq!(
+            statuses,
+            vec![
+                RewardStatus {
+                    order_id: "0x01".into(),
+                    market: "m1".into(),
+                    scoring: true,
+                    daily_rate: Some(Decimal::from(25)),
+                },
+                RewardStatus {
+                    order_id: "0x02".into(),
+                    market: "m2".into(),
+                    scoring: false,
+                    daily_rate: None,
+                },
+            ]
+        );
+    }
+
+    fn signed_order(salt: u64) -> SignedOrderRequest {
+        SignedOrderRequest {
+            salt,
+            maker: "0xMaker".into(),
+            signer: "0xSigner".into(),
+            taker: "0x0000000000000000000000000000000000000000".into(),
+            token_id: "1".into(),
+            maker_amount: "5000000".into(),
+            taker_amount: "10000000".into(),
+            expiration: "0".into(),
+            nonce: "0".into(),
+            fee_rate_bps: "0".into(),
+            side: "BUY".into(),
+            signature_type: 0,
+            signature: "0xsig".into(),
+        }
+    }
+
+    #[test]
+    fn test_batch_post_order_body() {
+        let body = vec![
+            PostOrder::new(signed_order(1), "key".into(), OrderType::GTC),
+            PostOrder::new(signed_order(2), "key".into(), OrderType::FOK),
+        ];
+
+        let value = serde_json::to_value(&body).unwrap();
+        assert_eq!(value.as_array().unwrap().len(), 2);
+        assert_eq!(value[0]["owner"], "key");
+        assert_eq!(value[0]["orderType"], "GTC");
+        assert_eq!(value[1]["orderType"], "FOK");
+        assert_eq!(value[1]["order"]["salt"], 2);
+        assert_eq!(value[1]["order"]["makerAmount"], "5000000");
+
+        let formatted = crate::utils::format_hmac_body(&body).unwrap();
+        assert!(formatted.starts_with(r#"[{"order": {"salt": 1, "maker": "0xMaker""#));
+        assert!(formatted.contains(r#""orderType": "GTC"}, {"order": {"salt": 2"#));
+        assert!(formatted.ends_with(r#""owner": "key", "orderType": "FOK"}]"#));
+    }
+
+    pub(crate) fn market_json() -> Value {
+        serde_json::json!({
+            "condition_id": "0xcond",
+            "tokens": [
+                {"token_id": "111", "outcome": "Yes"},
+                {"token_id": "222", "outcome": "No"}
+            ],
+            "rewards": {
+                "rates": null,
+                "min_size": 0,
+                "max_spread": 0
+            },
+            "min_incentive_size": null,
+            "max_incentive_spread": null,
+            "active": true,
+            "closed": false,
+            "question_id": "0xquestion",
+            "minimum_order_size": 5,
+            "minimum_tick_size": 0.01,
+            "description": "description",
+            "category": null,
+            "end_date_iso": "2025-01-01T00:00:00Z",
+            "game_start_time": null,
+            "question": "Will it happen?",
+            "market_slug": "will-it-happen",
+            "seconds_delay": 0,
+            "icon": "",
+            "fpmm": "",
+            "maker_base_fee": 0,
+            "taker_base_fee": 200
+        })
+    }
+
+    #[test]
+    fn test_market_reserializes_without_loss() {
+        let original = market_json();
+        let response: MarketsResponse = serde_json::from_value(serde_json::json!({
+            "limit": 1,
+            "count": 1,
+            "next_cursor": "LTE=",
+            "data": [original.clone()]
+        }))
+        .unwrap();
+
+        let ndjson = response.to_ndjson().unwrap();
+        assert_eq!(ndjson.lines().count(), 1);
+        assert!(ndjson.ends_with('\n'));
+        let exported: Value = serde_json::from_str(ndjson.trim_end()).unwrap();
+
+        // Decimals come back as strings and unset options as explicit nulls.
+        fn normalize(v: &Value) -> Value {
+            match v {
+                Value::Number(n) => Value::String(
+                    parse_decimal(&n.to_string())
+                        .unwrap()
+                        .normalize()
+                        .to_string(),
+                ),
+                Value::String(s) => match parse_decimal(s) {
+                    Ok(d) => Value::String(d.normalize().to_string()),
+                    Err(_) => v.clone(),
+                },
+                Value::Array(a) => Value::Array(a.iter().map(normalize).collect()),
+                Value::Object(o) => Value::Object(
+                    o.iter()
+                        .filter(|(_, v)| !v.is_null())
+                        .map(|(k, v)| (k.clone(), normalize(v)))
+                        .collect(),
+                ),
+                _ => v.clone(),
+            }
+        }
+        assert_eq!(normalize(&exported), normalize(&original));
+
+        let again: Market = serde_json::from_value(exported.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&again).unwrap(), exported);
+    }
+
+    #[test]
+    fn test_rewards_tolerant_numbers() {
+        let rewards = |multiplier: Value, epoch: Value| {
+            serde_json::from_value::<Rewards>(serde_json::json!({
+                "rates": null,
+                "min_size": 0,
+                "max_spread": 0,
+                "in_game_multiplier": multiplier,
+                "reward_epoch": epoch
+            }))
+            .unwrap()
+        };
+
+        let r = rewards(serde_json::json!(1.5), serde_json::json!(3));
+        assert_eq!(r.in_game_multiplier, Some(Decimal::new(15, 1)));
+        assert_eq!(r.reward_epoch, Some(Decimal::from(3)));
+
+        let r = rewards(serde_json::json!("2.25"), serde_json::json!("4"));
+        assert_eq!(r.in_game_multiplier, Some(Decimal::new(225, 2)));
+        assert_eq!(r.reward_epoch, Some(Decimal::from(4)));
+
+        let r = rewards(serde_json::json!(0.1), Value::Null);
+        assert_eq!(r.in_game_multiplier, Some(Decimal::new(1, 1)));
+        assert_eq!(r.reward_epoch, None);
+
+        let r: Rewards = serde_json::from_value(
+            serde_json::json!({"rates": null, "min_size": 0, "max_spread": 0}),
+        )
+        .unwrap();
+        assert_eq!(r.in_game_multiplier, None);
+    }
+
+    #[test]
+    fn test_estimate_reward_score() {
+        let book = |bid: i64, ask: i64| -> OrderBookSummary {
+            serde_json::from_value(serde_json::json!({
+                "market": "0xcond",
+                "asset_id": "1",
+                "hash": "",
+                "timestamp": "0",
+                "bids": [{"price": Decimal::new(bid, 2).to_string(), "size": "100"}],
+                "asks": [{"price": Decimal::new(ask, 2).to_string(), "size": "100"}]
+            }))
+            .unwrap()
+        };
+        let rewards: Rewards = serde_json::from_value(
+            serde_json::json!({"rates": null, "min_size": 10, "max_spread": 3}),
+        )
+        .unwrap();
+        let quote = |side: Side, cents: i64, size: i64| {
+            OrderArgs::new("1", Decimal::new(cents, 2), Decimal::from(size), side)
+        };
+        let score = |book: &OrderBookSummary, quotes: &[OrderArgs]| {
+            estimate_reward_score(book, quotes, &rewards)
+                .unwrap()
+                .round_dp(8)
+        };
+        let mid_book = book(48, 52);
+
+        let tight = score(
+            &mid_book,
+            &[quote(Side::BUY, 49, 100), quote(Side::SELL, 51, 100)],
+        );
+        let wide = score(
+            &mid_book,
+            &[quote(Side::BUY, 48, 100), quote(Side::SELL, 52, 100)],
+        );
+        let one_sided = score(&mid_book, &[quote(Side::BUY, 49, 100)]);
+        assert_eq!(tight, Decimal::new(4444444444, 8));
+        assert_eq!(wide, Decimal::new(1111111111, 8));
+        assert_eq!(one_sided, Decimal::new(1481481481, 8));
+        assert!(tight > one_sided && one_sided > wide);
+
+        assert_eq!(score(&mid_book, &[quote(Side::BUY, 49, 5)]), Decimal::ZERO);
+        assert_eq!(
+            score(&mid_book, &[quote(Side::BUY, 46, 100)]),
+            Decimal::ZERO
+        );
+
+        let edge_book = book(4, 6);
+        assert_eq!(
+            score(&edge_book, &[quote(Side::BUY, 4, 100)]),
+            Decimal::ZERO
+        );
+        assert!(
+            score(
+                &edge_book,
+                &[quote(Side::BUY, 4, 100), quote(Side::SELL, 6, 100)]
+            ) > Decimal::ZERO
+        );
+
+        let empty: OrderBookSummary = serde_json::from_value(serde_json::json!({
+            "market": "0xcond", "asset_id": "1", "hash": "", "timestamp": "0", "bids": [], "asks": []
+        }))
+        .unwrap();
+        assert_eq!(estimate_reward_score(&empty, &[], &rewards), None);
+    }
+
+    #[test]
+    fn test_order_book_with_null_sides() {
+        let book: OrderBookSummary = serde_json::from_value(serde_json::json!({
+            "market": "0xcond", "asset_id": "1", "hash": "", "timestamp": "0", "bids": null
+        }))
+        .unwrap();
+
+        assert!(book.bids.is_empty() && book.asks.is_empty());
+        assert_eq!(
+            book.top_of_book(),
+            TopOfBook {
+                best_bid: None,
+                best_ask: None
+            }
+        );
+        assert_eq!(book.midpoint(), None);
+    }
+
+    #[test]
+    fn test_market_complement() {
+        let market: Market = serde_json::from_value(market_json()).unwrap();
+        assert_eq!(market.complement("111"), Some("222"));
+        assert_eq!(market.complement("222"), Some("111"));
+        assert_eq!(market.complement("333"), None);
+    }
+
+    #[test]
+    fn test_market_yes_no_tokens() {
+        let mut json = market_json();
+        json["tokens"] = serde_json::json!([
+            {"token_id": "222", "outcome": "No"},
+            {"token_id": "111", "outcome": "Yes"}
+        ]);
+        let market: Market = serde_json::from_value(json.clone()).unwrap();
+        let (yes, no) = market.yes_no_tokens();
+        assert_eq!(
+            (yes.token_id.as_str(), no.token_id.as_str()),
+            ("111", "222")
+        );
+
+        json["tokens"] = serde_json::json!([
+            {"token_id": "222", "outcome": "Lakers"},
+            {"token_id": "111", "outcome": "Celtics"}
+        ]);
+        let market: Market = serde_json::from_value(json).unwrap();
+        let (yes, no) = market.yes_no_tokens();
+        assert_eq!(
+            (yes.token_id.as_str(), no.token_id.as_str()),
+            ("222", "111")
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_market_ends_at() {
+        use chrono::TimeZone;
+
+        let market: Market = serde_json::from_value(market_json()).unwrap();
+        let end = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(market.ends_at(), Some(end));
+
+        let mut json = market_json();
+        json["end_date_iso"] = Value::Null;
+        let undated: Market = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(undated.ends_at(), None);
+
+        json["end_date_iso"] = "2024-06-30".into();
+        let date_only: Market = serde_json::from_value(json).unwrap();
+        let june = Utc.with_ymd_and_hms(2024, 6, 30, 0, 0, 0).unwrap();
+        assert_eq!(date_only.ends_at(), Some(june));
+
+        let mut markets = vec![undated, market, date_only];
+        sort_markets_by_end_date(&mut markets);
+        let ends: Vec<_> = markets.iter().map(Market::ends_at).collect();
+        assert_eq!(ends, [Some(june), Some(end), None]);
+
+        let soon = markets_ending_between(markets, june, end);
+        assert_eq!(soon.len(), 1);
+        assert_eq!(soon[0].ends_at(), Some(june));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_market_game_start_at() {
+        use chrono::TimeZone;
+
+        let market = |start: Value| -> Market {
+            let mut json = market_json();
+            json["game_start_time"] = start;
+            serde_json::from_value(json).unwrap()
+        };
+        let early = market("2024-11-10 01:00:00+00".into());
+        let late = market("2024-11-10T03:30:00Z".into());
+        let undated = market(Value::Null);
+        assert_eq!(
+            early.game_start_at(),
+            Some(Utc.with_ymd_and_hms(2024, 11, 10, 1, 0, 0).unwrap())
+        );
+        assert_eq!(
+            late.game_start_at(),
+            Some(Utc.with_ymd_and_hms(2024, 11, 10, 3, 30, 0).unwrap())
+        );
+        assert_eq!(undated.game_start_at(), None);
+
+        let from = Utc.with_ymd_and_hms(2024, 11, 10, 0, 30, 0).unwrap();
+        let to = from + chrono::Duration::hours(1);
+        let starting = markets_starting_between(vec![early, late, undated], from, to);
+        assert_eq!(starting.len(), 1);
+        assert_eq!(
+            starting[0].game_start_time.as_deref(),
+            Some("2024-11-10 01:00:00+00")
+        );
+    }
+
+    #[test]
+    fn test_market_status() {
+        let mut json = market_json();
+        json["accepting_orders"] = true.into();
+        let market: Market = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(market.status(), MarketStatus::Tradeable);
+        assert!(market.is_tradeable());
+
+        json["closed"] = true.into();
+        let market: Market = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(market.status(), MarketStatus::Closed);
+        assert!(!market.is_tradeable());
+
+        json["closed"] = false.into();
+        json["accepting_orders"] = false.into();
+        let market: Market = serde_json::from_value(json).unwrap();
+        assert_eq!(market.status(), MarketStatus::NotAcceptingOrders);
+    }
+
+    #[test]
+    fn test_fee_rate_response() {
+        let resp: FeeRateResponse = serde_json::from_str(r#"{"base_fee": 0}"#).unwrap();
+        assert_eq!(resp.base_fee, 0);
+    }
+
+    #[test]
+    fn test_order_args_try_new_validates_token_id() {
+        let price = Decimal::from_str("0.55").unwrap();
+        assert!(OrderArgs::try_new("111", price, Decimal::TEN, Side::BUY).is_ok());
+        let err = OrderArgs::try_new("11l", price, Decimal::TEN, Side::BUY).unwrap_err();
+        assert!(err.to_string().contains("Incorrect tokenId format"));
+    }
+
+    #[test]
+    fn test_order_args_from_notional() {
+        let price = Decimal::from_str("0.40").unwrap();
+        let args = OrderArgs::from_notional("111", price, Decimal::from(50), Side::BUY).unwrap();
+        assert_eq!(args.size, Decimal::from(125));
+        assert_eq!(args.price, price);
+
+        assert!(OrderArgs::from_notional("111", Decimal::ZERO, Decimal::TEN, Side::BUY).is_err());
+    }
+
+    #[test]
+    fn test_extra_order_args_taker() {
+        assert_eq!(ExtraOrderArgs::default().taker_address(), Address::ZERO);
+
+        let extras = ExtraOrderArgs::default()
+            .with_taker("0x4bfb41d5b3570defd03c39a9a4d8de6bd8b8982e")
+            .unwrap();
+        assert_eq!(
+            extras.taker_address().to_checksum(None),
+            "0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E"
+        );
+
+        for bad in ["", "0x1234", "not an address"] {
+            let err = ExtraOrderArgs::default().with_taker(bad).unwrap_err();
+            assert!(err.to_string().contains("Invalid taker address"), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_private_order_carries_taker() {
+        assert!(ExtraOrderArgs::private_to(Address::ZERO).is_err());
+
+        let taker = Address::from_str("0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E").unwrap();
+        let args = OrderArgs::new("1234", Decimal::new(5, 1), Decimal::TEN, Side::BUY);
+        let extras = ExtraOrderArgs::private_to(taker).unwrap();
+
+        let order = crate::orders::tests::sign_test_order(&args, Some(extras));
+        assert_eq!(order.taker, taker.to_checksum(None));
+    }
+
+    #[test]
+    fn test_order_args_round_trip() {
+        let args = OrderArgs::new(
+            "111",
+            Decimal::from_str("0.55").unwrap(),
+            Decimal::from_str("12.50").unwrap(),
+            Side::SELL,
+        );
+        let json = serde_json::to_value(&args).unwrap();
+        assert_eq!(json["price"], "0.55");
+        assert_eq!(json["size"], "12.50");
+
+        let back: OrderArgs = serde_json::from_value(json).unwrap();
+        assert_eq!(back.token_id, args.token_id);
+        assert_eq!(back.price, args.price);
+        assert_eq!(back.size, args.size);
+        assert_eq!(back.side, args.side);
+
+        let market_args = MarketOrderArgs {
+            token_id: "111".into(),
+            amount: Decimal::from_str("100.25").unwrap(),
+        };
+        let back: MarketOrderArgs =
+            serde_json::from_str(&serde_json::to_string(&market_args).unwrap()).unwrap();
+        assert_eq!(back.amount, market_args.amount);
+
+        let extras = ExtraOrderArgs {
+            fee_rate_bps: Some(10),
+            nonce: U256::from(7),
+            ..Default::default()
+        };
+        let back: ExtraOrderArgs =
+            serde_json::from_str(&serde_json::to_string(&extras).unwrap()).unwrap();
+        assert_eq!(back.fee_rate_bps, Some(10));
+        assert_eq!(back.nonce, U256::from(7));
+        assert_eq!(back.taker, extras.taker);
+
+        let extras = ExtraOrderArgs::default()
+            .with_taker("0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E")
+            .unwrap();
+        let back: ExtraOrderArgs =
+            serde_json::from_str(&serde_json::to_string(&extras).unwrap()).unwrap();
+        assert_eq!(back.taker, extras.taker);
+
+        let options = CreateOrderOptions {
+            tick_size: Some(Decimal::from_str("0.001").unwrap()),
+            neg_risk: Some(true),
+            max_book_levels: Some(3),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&options).unwrap();
+        assert_eq!(json["tick_size"], "0.001");
+        let back: CreateOrderOptions = serde_json::from_value(json).unwrap();
+        assert_eq!(back.tick_size, options.tick_size);
+        assert_eq!(back.neg_risk, Some(true));
+        assert_eq!(back.max_book_levels, Some(3));
+        assert_eq!(back.max_slippage_bps, None);
+    }
+
+    const ME: &str = "0x00000000000000000000000000000000000000aa";
+    const OTHER: &str = "0x00000000000000000000000000000000000000bb";
+
+    fn trade(id: &str, taker: &str, maker: &str) -> Trade {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "taker_order_id": "0xtaker",
+            "market": "0xcond",
+            "asset_id": "111",
+            "side": "BUY",
+            "size": "10",
+            "fee_rate_bps": "0",
+            "price": "0.5",
+            "status": "MATCHED",
+            "match_time": "1700000000",
+            "last_update": "1700000000",
+            "outcome": "Yes",
+            "bucket_index": 0,
+            "owner": "owner",
+            "maker_address": taker,
+            "maker_orders": [{
+                "order_id": "0xmaker",
+                "maker_address": maker,
+                "owner": "owner",
+                "matched_amount": "10",
+                "fee_rate_bps": "0",
+                "price": "0.5",
+                "asset_id": "111",
+                "outcome": "Yes",
+                "side": "SELL"
+            }],
+            "transaction_hash": "0xhash",
+            "trader_side": "TAKER"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_trade_roles() {
+        let trades = [
+            trade("1", ME, OTHER),
+            trade("2", OTHER, ME),
+            trade("3", OTHER, OTHER),
+        ];
+        let me = ME.to_uppercase().replacen("0X", "0x", 1);
+
+        let roles = trades.iter().map(|t| t.role(&me)).collect::<Vec<_>>();
+        assert_eq!(
+            roles,
+            vec![Some(TradeRole::Taker), Some(TradeRole::Maker), None]
+        );
+    }
+
+    #[test]
+    fn test_expiration_at() {
+        assert_eq!(Expiration::At(1_700_000_000).to_timestamp(), 1_700_000_000);
+        assert_eq!(Expiration::from(42).to_timestamp(), 42);
+        assert_eq!(Expiration::None.to_timestamp(), 0);
+    }
+
+    #[test]
+    fn test_expiration_in() {
+        let before = get_current_unix_time_secs();
+        let ts = Expiration::In(Duration::from_secs(300)).to_timestamp();
+        let after = get_current_unix_time_secs();
+
+        assert!(ts >= before + 300 && ts <= after + 300);
+    }
+
+    #[test]
+    fn test_side_opposite() {
+        assert_eq!(Side::BUY.opposite(), Side::SELL);
+        assert_eq!(Side::SELL.opposite(), Side::BUY);
+    }
+
+    #[test]
+    fn test_side_from_str() {
+        for (input, side) in [
+            ("BUY", Side::BUY),
+            ("buy", Side::BUY),
+            ("0", Side::BUY),
+            ("SELL", Side::SELL),
+            ("sell", Side::SELL),
+            ("1", Side::SELL),
+        ] {
+            assert_eq!(Side::from_str(input).unwrap(), side);
+        }
+        assert!(Side::from_str("hold").is_err());
+        assert!(Side::from_str("2").is_err());
+    }
+
+    #[test]
+    fn test_price_response_with_price() {
+        let resp: PriceResponse = serde_json::from_str(r#"{"price": "0.52"}"#).unwrap();
+        assert_eq!(resp.price, Some(Decimal::from_str("0.52").unwrap()));
+    }
+}
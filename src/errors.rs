@@ -0,0 +1,56 @@
+use rust_decimal::Decimal;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceBound {
+    Min,
+    Max,
+}
+
+/// Returned when an order price falls outside `[tick_size, 1 - tick_size]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PriceOutOfRange {
+    pub price: Decimal,
+    pub min_price: Decimal,
+    pub max_price: Decimal,
+    pub violated: PriceBound,
+}
+
+impl fmt::Display for PriceOutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.violated {
+            PriceBound::Min => write!(
+                f,
+                "Price {} is below the minimum price {} (allowed range {}..={})",
+                self.price, self.min_price, self.min_price, self.max_price
+            ),
+            PriceBound::Max => write!(
+                f,
+                "Price {} is above the maximum price {} (allowed range {}..={})",
+                self.price, self.max_price, self.min_price, self.max_price
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PriceOutOfRange {}
+
+/// Returned when the book cannot fill a market order. `available_amount` is what
+/// the book could fill, so a smaller order can be placed instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InsufficientLiquidity {
+    pub requested_amount: Decimal,
+    pub available_amount: Decimal,
+}
+
+impl fmt::Display for InsufficientLiquidity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Not enough liquidity to create market order with amount {}, only {} available",
+            self.requested_amount, self.available_amount
+        )
+    }
+}
+
+impl std::error::Error for InsufficientLiquidity {}
@@ -1,9 +1,12 @@
-use crate::ClientResult;
-use alloy_primitives::U256;
-use alloy_primitives::{hex::encode_prefixed, Address};
+use crate::config::exchange_domain_version;
+use crate::utils::parse_token_id;
+use crate::{ClientResult, Side, SignedOrderRequest};
+use alloy_primitives::{hex, U256};
+use alloy_primitives::{hex::encode_prefixed, Address, B256};
 use alloy_signer::{Signer, SignerSync};
-use alloy_sol_types::{eip712_domain, sol};
+use alloy_sol_types::{eip712_domain, sol, Eip712Domain, SolStruct};
 use anyhow::Context;
+use std::str::FromStr;
 
 pub trait EthSigner: Signer + SignerSync + Send + Sync {}
 
@@ -64,19 +67,69 @@ pub fn sign_clob_auth_message(
     Ok(encode_prefixed(val.as_bytes()))
 }
 
+fn order_domain(chain_id: u64, verifying_contract: Address, version: &str) -> Eip712Domain {
+    Eip712Domain::new(
+        Some("Polymarket CTF Exchange".into()),
+        Some(version.to_owned().into()),
+        Some(U256::from(chain_id)),
+        Some(verifying_contract),
+        None,
+    )
+}
+
+impl TryFrom<&SignedOrderRequest> for Order {
+    type Error = anyhow::Error;
+
+    fn try_from(o: &SignedOrderRequest) -> ClientResult<Self> {
+        Ok(Order {
+            salt: U256::from(o.salt),
+            maker: Address::from_str(&o.maker).context("Invalid maker address")?,
+            signer: Address::from_str(&o.signer).context("Invalid signer address")?,
+            taker: Address::from_str(&o.taker).context("Invalid taker address")?,
+            tokenId: parse_token_id(&o.token_id)?,
+            makerAmount: U256::from_str(&o.maker_amount).context("Invalid maker amount")?,
+            takerAmount: U256::from_str(&o.taker_amount).context("Invalid taker amount")?,
+            expiration: U256::from_str(&o.expiration).context("Invalid expiration")?,
+            nonce: U256::from_str(&o.nonce).context("Invalid nonce")?,
+            feeRateBps: U256::from_str(&o.fee_rate_bps).context("Invalid fee rate")?,
+            side: Side::from_str(&o.side)? as u8,
+            signatureType: o.signature_type,
+        })
+    }
+}
+
+/// EIP-712 hash of `order` as a `0x` hex string. This is the order id the CLOB
+/// assigns on post, so it can be used to track orders before they are sent.
+pub fn order_hash(
+    order: &SignedOrderRequest,
+    chain_id: u64,
+    exchange: Address,
+) -> ClientResult<String> {
+    let order = Order::try_from(order)?;
+    let version = exchange_domain_version(chain_id, exchange);
+    Ok(encode_prefixed(order_digest(
+        &order, chain_id, exchange, &version,
+    )))
+}
+
+/// EIP-712 signing digest of `order` for the given exchange domain.
+pub(crate) fn order_digest(
+    order: &Order,
+    chain_id: u64,
+    exchange: Address,
+    domain_version: &str,
+) -> B256 {
+    order.eip712_signing_hash(&order_domain(chain_id, exchange, domain_version))
+}
+
 pub fn sign_order_message(
     signer: &(impl EthSigner + Sized),
     order: Order,
     chain_id: u64,
     verifying_contract: Address,
+    domain_version: &str,
 ) -> ClientResult<String> {
-    let domain = eip712_domain!(
-        name: "Polymarket CTF Exchange",
-        version: "1",
-        chain_id: chain_id,
-        verifying_contract: verifying_contract,
-
-    );
+    let domain = order_domain(chain_id, verifying_contract, domain_version);
 
     let val = signer
         .sign_typed_data_sync(&order, &domain)
@@ -84,3 +137,244 @@ pub fn sign_order_message(
 
     Ok(encode_prefixed(val.as_bytes()))
 }
+
+/// Encoding of a signature's recovery byte. Orders are signed with `Electrum`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryIdFormat {
+    /// `v` is 0 or 1.
+    Parity,
+    /// `v` is 27 or 28.
+    Electrum,
+}
+
+/// The `(r, s, v)` components of a 65 byte ECDSA signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignatureParts {
+    pub r: B256,
+    pub s: B256,
+    pub v: u8,
+}
+
+impl SignatureParts {
+    /// Splits a hex encoded `r || s || v` signature, such as
+    /// `SignedOrderRequest::signature`.
+    pub fn from_hex(signature: &str) -> ClientResult<Self> {
+        let bytes = hex::decode(signature).context("Invalid signature hex")?;
+        let bytes: [u8; 65] = bytes
+            .try_into()
+            .map_err(|b: Vec<u8>| anyhow::anyhow!("Signature must be 65 bytes, got {}", b.len()))?;
+        let parts = Self {
+            r: B256::from_slice(&bytes[..32]),
+            s: B256::from_slice(&bytes[32..64]),
+            v: bytes[64],
+        };
+        parts.parity()?;
+        Ok(parts)
+    }
+
+    fn parity(&self) -> ClientResult<u8> {
+        match self.v {
+            0 | 1 => Ok(self.v),
+            27 | 28 => Ok(self.v - 27),
+            v => Err(anyhow::anyhow!("Unsupported signature v value {v}")),
+        }
+    }
+
+    /// Same signature with `v` re-encoded in `format`.
+    pub fn normalized(self, format: RecoveryIdFormat) -> ClientResult<Self> {
+        let parity = self.parity()?;
+        let v = match format {
+            RecoveryIdFormat::Parity => parity,
+            RecoveryIdFormat::Electrum => parity + 27,
+        };
+        Ok(Self { v, ..self })
+    }
+
+    /// `0x` prefixed `r || s || v` hex string.
+    pub fn to_hex(&self) -> String {
+        let mut bytes = [0u8; 65];
+        bytes[..32].copy_from_slice(self.r.as_slice());
+        bytes[32..64].copy_from_slice(self.s.as_slice());
+        bytes[64] = self.v;
+        encode_prefixed(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orders::tests::{sign_test_order, test_signer};
+    use crate::OrderArgs;
+    use alloy_primitives::{keccak256, PrimitiveSignature};
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn test_order_hash_matches_signed_digest() {
+        let address = test_signer().address();
+        let args = OrderArgs::new(
+            "1234",
+            Decimal::from_str("0.25").unwrap(),
+            Decimal::from(4),
+            Side::SELL,
+        );
+        let order = sign_test_order(&args, None);
+        let exchange = Address::from_str("0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E").unwrap();
+
+        let hash = order_hash(&order, 137, exchange).unwrap();
+        assert_eq!(hash.len(), 66);
+        assert_eq!(hash, order_hash(&order, 137, exchange).unwrap());
+        assert_ne!(hash, order_hash(&order, 80002, exchange).unwrap());
+
+        let signature = PrimitiveSignature::from_str(&order.signature).unwrap();
+        let recovered = signature
+            .recover_address_from_prehash(&B256::from_str(&hash).unwrap())
+            .unwrap();
+        assert_eq!(recovered, address);
+    }
+
+    #[test]
+    fn test_order_hash_known_vector() {
+        // The expected digest is rebuilt below straight from EIP-712: keccak256
+        // over `0x1901 || domainSeparator || hashStruct(order)`, with the type
+        // strings of `ORDER_TYPEHASH` in the CTF Exchange contract
+        // (src/exchange/libraries/OrderStructs.sol) and its `EIP712("Polymarket
+        // CTF Exchange", "1")` domain, not through the `sol!` derive under test.
+        let order = SignedOrderRequest {
+            salt: 479249096354,
+            maker: "0x0000000000000000000000000000000000000001".into(),
+            signer: "0x0000000000000000000000000000000000000002".into(),
+            taker: "0x0000000000000000000000000000000000000000".into(),
+            token_id: "1234".into(),
+            maker_amount: "100000000".into(),
+            taker_amount: "50000000".into(),
+            expiration: "0".into(),
+            nonce: "0".into(),
+            fee_rate_bps: "100".into(),
+            side: "BUY".into(),
+            signature_type: 0,
+            signature: String::new(),
+        };
+        let exchange = Address::from_str("0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E").unwrap();
+
+        let word = |n: u64| B256::from(U256::from(n)).0;
+        let address = |a: Address| B256::left_padding_from(a.as_slice()).0;
+        let domain_separator = keccak256(
+            [
+                keccak256(
+                    "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)",
+                )
+                .0,
+                keccak256("Polymarket CTF Exchange").0,
+                keccak256("1").0,
+                word(137),
+                address(exchange),
+            ]
+            .concat(),
+        );
+        let struct_hash = keccak256(
+            [
+                keccak256(
+                    "Order(uint256 salt,address maker,address signer,address taker,\
+                     uint256 tokenId,uint256 makerAmount,uint256 takerAmount,\
+                     uint256 expiration,uint256 nonce,uint256 feeRateBps,uint8 side,\
+                     uint8 signatureType)",
+                )
+                .0,
+                word(479249096354),
+                address(Address::with_last_byte(1)),
+                address(Address::with_last_byte(2)),
+                address(Address::ZERO),
+                word(1234),
+                word(100_000_000),
+                word(50_000_000),
+                word(0),
+                word(0),
+                word(100),
+                word(0),
+                word(0),
+            ]
+            .concat(),
+        );
+        let digest =
+            keccak256([&[0x19, 0x01][..], &domain_separator[..], &struct_hash[..]].concat());
+
+        let expected = "0x17e8935400cd0ab47aa25fedc98226967469001366c40c27594e15313997bcae";
+        assert_eq!(encode_prefixed(digest), expected);
+        assert_eq!(order_hash(&order, 137, exchange).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_signed_order_survives_json_round_trip() {
+        let address = test_signer().address();
+        let args = OrderArgs::new(
+            "1234",
+            Decimal::from_str("0.4").unwrap(),
+            Decimal::from(10),
+            Side::BUY,
+        );
+        let order = sign_test_order(&args, None);
+        let exchange = Address::from_str("0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E").unwrap();
+
+        let stored = serde_json::to_string(&order).unwrap();
+        let restored: SignedOrderRequest = serde_json::from_str(&stored).unwrap();
+
+        assert_eq!(serde_json::to_string(&restored).unwrap(), stored);
+        let hash = order_hash(&restored, 137, exchange).unwrap();
+        assert_eq!(hash, order_hash(&order, 137, exchange).unwrap());
+        let signature = PrimitiveSignature::from_str(&restored.signature).unwrap();
+        let recovered = signature
+            .recover_address_from_prehash(&B256::from_str(&hash).unwrap())
+            .unwrap();
+        assert_eq!(recovered, address);
+    }
+
+    #[test]
+    fn test_order_domain_version_per_exchange() {
+        let exchange = Address::from_str("0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E").unwrap();
+        let neg_risk = Address::from_str("0xC5d563A36AE78145C45a50134d48A1215220f80a").unwrap();
+        assert_eq!(exchange_domain_version(137, exchange), "1");
+        assert_eq!(exchange_domain_version(137, neg_risk), "1");
+        assert_eq!(exchange_domain_version(137, Address::ZERO), "1");
+
+        let domain = order_domain(137, exchange, &exchange_domain_version(137, exchange));
+        let expected = eip712_domain!(
+            name: "Polymarket CTF Exchange",
+            version: "1",
+            chain_id: 137,
+            verifying_contract: exchange,
+        );
+        assert_eq!(domain, expected);
+        assert_ne!(
+            domain.separator(),
+            order_domain(137, exchange, "2").separator()
+        );
+    }
+
+    #[test]
+    fn test_signature_parts_round_trip() {
+        let args = OrderArgs::new(
+            "1234",
+            Decimal::from_str("0.4").unwrap(),
+            Decimal::from(10),
+            Side::BUY,
+        );
+        let order = sign_test_order(&args, None);
+
+        let parts = SignatureParts::from_hex(&order.signature).unwrap();
+        assert!(parts.v == 27 || parts.v == 28);
+        assert_eq!(parts.to_hex(), order.signature);
+
+        let parity = parts.normalized(RecoveryIdFormat::Parity).unwrap();
+        assert_eq!(parity.v, parts.v - 27);
+        assert_eq!((parity.r, parity.s), (parts.r, parts.s));
+        let back = SignatureParts::from_hex(&parity.to_hex())
+            .unwrap()
+            .normalized(RecoveryIdFormat::Electrum)
+            .unwrap();
+        assert_eq!(back.to_hex(), order.signature);
+
+        assert!(SignatureParts::from_hex("0x1234").is_err());
+        let bad = SignatureParts { v: 5, ..parts };
+        assert!(SignatureParts::from_hex(&bad.to_hex()).is_err());
+    }
+}
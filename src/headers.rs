@@ -41,30 +41,35 @@ pub fn create_l2_headers<T>(
 where
     T: ?Sized + Serialize,
 {
-    let address = encode_prefixed(signer.address().as_slice());
-    let timestamp = get_current_unix_time_secs();
-
     let body_str = match body {
         None => None,
         Some(b) => Some(format_hmac_body(b)?),
     };
 
-    let hmac_signature = build_hmac_signature_from_str(
-        &api_creds.secret,
-        timestamp,
-        method,
-        req_path,
-        body_str.as_deref(),
-    )?;
+    let headers =
+        create_l2_headers_for_body(signer, api_creds, method, req_path, body_str.as_deref())?;
+    Ok((headers, body_str))
+}
 
-    Ok((
-        HashMap::from([
-            (POLY_ADDR_HEADER, address),
-            (POLY_SIG_HEADER, hmac_signature),
-            (POLY_TS_HEADER, timestamp.to_string()),
-            (POLY_API_KEY_HEADER, api_creds.api_key.clone()),
-            (POLY_PASS_HEADER, api_creds.passphrase.clone()),
-        ]),
-        body_str,
-    ))
+/// L2 headers signing `body` exactly as given, without re-serializing it.
+pub fn create_l2_headers_for_body(
+    signer: &impl EthSigner,
+    api_creds: &ApiCreds,
+    method: &str,
+    req_path: &str,
+    body: Option<&str>,
+) -> Result<Headers> {
+    let address = encode_prefixed(signer.address().as_slice());
+    let timestamp = get_current_unix_time_secs();
+
+    let hmac_signature =
+        build_hmac_signature_from_str(&api_creds.secret, timestamp, method, req_path, body)?;
+
+    Ok(HashMap::from([
+        (POLY_ADDR_HEADER, address),
+        (POLY_SIG_HEADER, hmac_signature),
+        (POLY_TS_HEADER, timestamp.to_string()),
+        (POLY_API_KEY_HEADER, api_creds.api_key.clone()),
+        (POLY_PASS_HEADER, api_creds.passphrase.clone()),
+    ]))
 }
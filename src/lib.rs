@@ -2,40 +2,109 @@ use alloy_primitives::hex::encode_prefixed;
 pub use alloy_primitives::{Address, U256};
 use alloy_signer_local::PrivateKeySigner;
 pub use anyhow::{anyhow, Context, Result as ClientResult};
-use config::get_contract_config;
-use orders::OrderBuilder;
-use orders::SignedOrderRequest;
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
+pub use config::SUPPORTED_CHAINS;
+use config::{check_chain_supported, get_contract_config};
+use futures_util::{future, stream, Future, Stream, StreamExt, TryStreamExt};
 use reqwest::header::HeaderName;
 use reqwest::Client;
-use reqwest::Method;
+pub use reqwest::Method;
 use reqwest::RequestBuilder;
+use reqwest::Response;
+use reqwest::StatusCode;
 use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 pub use serde_json::Value;
-use std::collections::HashMap;
-
-// #[cfg(test)]
-// mod tests;
-
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{RwLock, Semaphore};
+
+#[cfg(test)]
+mod tests;
+
+mod book;
+mod cache;
 mod config;
+mod contracts;
+mod creds;
 mod data;
+mod errors;
 mod eth_utils;
 mod headers;
+mod middleware;
 mod orders;
+mod transport;
 mod utils;
-
+#[cfg(feature = "ws")]
+mod ws;
+
+pub use book::{LocalOrderBook, PriceChange, PriceChangeEvent, TickSizeChangeEvent};
+pub use cache::{TokenCache, TtlCache};
+pub use contracts::{
+    approve_collateral_call, approve_conditional_tokens_call, increment_nonce_call,
+    merge_positions_call, split_position_call, ContractCall,
+};
+pub use creds::CredsCipher;
+use creds::{load_creds, store_creds};
 pub use data::*;
-pub use eth_utils::EthSigner;
-use headers::{create_l1_headers, create_l2_headers};
-pub use orders::SigType;
-
+pub use errors::{InsufficientLiquidity, PriceBound, PriceOutOfRange};
+pub use eth_utils::{order_hash, EthSigner, Order, RecoveryIdFormat, SignatureParts};
+use headers::{create_l1_headers, create_l2_headers, create_l2_headers_for_body};
+pub use middleware::{Middleware, RequestCounter};
+use orders::{
+    check_book_age, check_market_order_liquidity, check_price_through_book, check_tick_size,
+    improved_price, round_to_tick,
+};
+pub use orders::{
+    check_price_in_range, executable_price, finalize_order, sign_order, snap_to_tick, vwap,
+    MarketPriceFill, OrderBuilder, RoundConfig, SigType, SignedOrderRequest, UnsignedOrder,
+    BASE_PRECISION, QUOTE_PRECISION, USDC_DECIMALS,
+};
+use transport::{buffer_response_body, check_status, decode_json};
+pub use transport::{Transport, TransportFuture};
+pub use utils::{
+    complement_price, implied_probability, parse_token_id, to_ndjson, token_id_to_decimal_string,
+};
+use utils::{get_current_unix_time_millis, get_current_unix_time_secs, normalize_order_hash};
+#[cfg(feature = "ws")]
+pub use ws::{WsConfig, WsManager, MARKET_WS_URL};
+
+/// Client for the CLOB API.
+///
+/// `ClobClient` is `Send + Sync` and every method takes `&self` apart from
+/// configuration setters, so one client can be wrapped in an `Arc` and shared by
+/// many tasks. Caches, the clock skew estimate and the request limit are shared
+/// by all of them.
 #[derive(Default)]
 pub struct ClobClient {
     host: String,
+    base_path: String,
     http_client: Client,
     signer: Option<Box<dyn EthSigner>>,
     chain_id: Option<u64>,
     api_creds: Option<ApiCreds>,
     order_builder: Option<OrderBuilder>,
+    tick_sizes: Arc<TokenCache<Decimal>>,
+    neg_risks: Arc<TokenCache<bool>>,
+    fee_rates: Arc<TokenCache<u32>>,
+    http_config: HttpConfig,
+    data_api_host: Option<String>,
+    transport: Option<Arc<dyn Transport>>,
+    markets: Option<Arc<TtlCache<Market>>>,
+    clock_skew: Arc<Mutex<Option<i64>>>,
+    max_clock_skew: Option<Duration>,
+    request_permits: Option<Arc<Semaphore>>,
+    middleware: Vec<Arc<dyn Middleware>>,
+    shut_down: AtomicBool,
+    in_flight: RwLock<()>,
+    extra_api_creds: Vec<ApiCreds>,
+    next_api_creds: AtomicUsize,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -65,9 +134,85 @@ impl ClientSignerConfig {
     }
 }
 
+/// Connection pool and response settings for the internal HTTP client.
+///
+/// The defaults match reqwest's: an unbounded number of idle connections per
+/// host, closed after 90 seconds of inactivity. Latency sensitive users polling
+/// a single host may want a longer idle timeout to keep TLS sessions warm.
+/// Response bodies are unbounded unless `max_response_bytes` is set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HttpConfig {
+    pub pool_max_idle_per_host: usize,
+    pub pool_idle_timeout: Option<Duration>,
+    pub max_response_bytes: Option<usize>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            pool_max_idle_per_host: usize::MAX,
+            pool_idle_timeout: Some(Duration::from_secs(90)),
+            max_response_bytes: None,
+        }
+    }
+}
+
+impl HttpConfig {
+    pub fn with_pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = max;
+        self
+    }
+
+    /// `None` keeps idle connections open indefinitely.
+    pub fn with_pool_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.pool_idle_timeout = timeout;
+        self
+    }
+
+    /// Fail requests whose response body is larger than `max` bytes.
+    pub fn with_max_response_bytes(mut self, max: Option<usize>) -> Self {
+        self.max_response_bytes = max;
+        self
+    }
+
+    fn build_client(&self) -> Client {
+        Client::builder()
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .pool_idle_timeout(self.pool_idle_timeout)
+            .build()
+            .expect("Failed to build HTTP client")
+    }
+}
+
+const DATA_API_HOST: &str = "https://data-api.polymarket.com";
 const INITIAL_CURSOR: &str = "MA==";
 const END_CURSOR: &str = "LTE=";
 
+/// Lines up a batch endpoint's per-token map with the requested `token_ids`.
+fn align_to_token_ids<V: Clone>(
+    token_ids: &[String],
+    values: HashMap<String, V>,
+) -> Vec<(String, Option<V>)> {
+    token_ids
+        .iter()
+        .map(|id| (id.clone(), values.get(id).cloned()))
+        .collect()
+}
+
+/// The `next_cursor` a page returned, or `None` once pagination should stop:
+/// the end marker, a missing or empty cursor, or one already requested (which
+/// would otherwise loop forever).
+fn next_page_cursor(next_cursor: Option<&str>, seen: &mut HashSet<String>) -> Option<String> {
+    let cursor = next_cursor.filter(|c| !is_cursor_end(Some(c)))?;
+    seen.insert(cursor.to_owned()).then(|| cursor.to_owned())
+}
+
+/// Whether a page's `next_cursor` marks the last page. Endpoints signal this
+/// with the `LTE=` marker, an empty string or no cursor at all.
+pub fn is_cursor_end(next_cursor: Option<&str>) -> bool {
+    next_cursor.is_none_or(|c| c.is_empty() || c == END_CURSOR)
+}
+
 impl ClobClient {
     // TODO: initial headers, gzip
     pub fn new(host: &str) -> Self {
@@ -99,6 +244,7 @@ impl ClobClient {
             chain_id: Some(chain_id),
             api_creds: None,
             order_builder: Some(order_builder),
+            ..Default::default()
         }
     }
 
@@ -131,18 +277,172 @@ impl ClobClient {
             chain_id: Some(chain_id),
             api_creds: Some(api_creds),
             order_builder: Some(order_builder),
+            ..Default::default()
         }
     }
 
-    fn build_order_builder(
-        signer: &Box<PrivateKeySigner>,
-        config: ClientSignerConfig,
-    ) -> OrderBuilder {
+    fn build_order_builder(signer: &PrivateKeySigner, config: ClientSignerConfig) -> OrderBuilder {
         let funder = config.funder.unwrap_or_else(|| signer.address());
-        OrderBuilder::new(signer.clone(), Some(config.signature_type), Some(funder))
+        OrderBuilder::new(
+            Box::new(signer.clone()),
+            Some(config.signature_type),
+            Some(funder),
+        )
+    }
+    pub fn with_http_config(mut self, http_config: HttpConfig) -> Self {
+        self.http_client = http_config.build_client();
+        self.http_config = http_config;
+        self
+    }
+
+    pub fn http_config(&self) -> &HttpConfig {
+        &self.http_config
+    }
+
+    /// Prefix inserted between the host and every CLOB endpoint, e.g. `/clob/v2`
+    /// for gateways that mount the API under a sub path. Signatures still cover
+    /// the bare endpoint.
+    pub fn with_base_path(mut self, base_path: &str) -> Self {
+        self.base_path = base_path.trim_end_matches('/').to_owned();
+        self
+    }
+
+    fn endpoint_url(&self, endpoint: &str) -> String {
+        format!("{}{}{endpoint}", self.host, self.base_path)
+    }
+
+    /// Serves `get_market` from memory for `ttl` after each fetch.
+    pub fn with_market_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.markets = Some(Arc::new(TtlCache::new(ttl)));
+        self
+    }
+
+    /// Refuses to sign L2 requests while the last `clock_skew` measurement is
+    /// larger than `max`. `None` turns the check off.
+    pub fn with_max_clock_skew(mut self, max: Option<Duration>) -> Self {
+        self.max_clock_skew = max;
+        self
+    }
+
+    /// Drops a cached market so the next `get_market` refetches it.
+    pub fn invalidate_market(&self, condition_id: &str) {
+        if let Some(markets) = &self.markets {
+            markets.remove(condition_id);
+        }
+    }
+
+    /// Replaces the HTTP transport, e.g. with a scripted one in tests.
+    pub fn with_transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
     }
-    pub fn set_api_creds(&mut self, api_creds: ApiCreds) {
+
+    /// Adds a hook run around every request, after any added before it.
+    pub fn with_middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Caps the number of requests waiting on the server at once, counting each
+    /// until its response body has been read. The limit is shared by every
+    /// method of this client, so concurrent helpers running side by side stay
+    /// within it together.
+    pub fn with_max_concurrent_requests(mut self, max: usize) -> Self {
+        self.request_permits = Some(Arc::new(Semaphore::new(max)));
+        self
+    }
+
+    /// Stops the client from starting new requests; every call that would send
+    /// one fails from now on. Requests already underway are unaffected, use
+    /// [`ClobClient::drain`] to wait for them.
+    pub fn shutdown(&self) {
+        self.shut_down.store(true, Ordering::Release);
+    }
+
+    pub fn is_shut_down(&self) -> bool {
+        self.shut_down.load(Ordering::Acquire)
+    }
+
+    /// Waits until every request already sent has its response. Meant to be
+    /// called after [`ClobClient::shutdown`], otherwise new requests may keep
+    /// it waiting.
+    pub async fn drain(&self) {
+        let _ = self.in_flight.write().await;
+    }
+
+    pub fn with_data_api_host(mut self, data_api_host: &str) -> Self {
+        self.data_api_host = Some(data_api_host.to_owned());
+        self
+    }
+
+    pub fn data_api_host(&self) -> &str {
+        self.data_api_host.as_deref().unwrap_or(DATA_API_HOST)
+    }
+
+    /// Replaces the API credentials, dropping any pool set by
+    /// [`ClobClient::set_api_creds_pool`].
+    pub fn set_api_creds(&mut self, api_creds: ApiCreds) -> ClientResult<()> {
+        api_creds.validate()?;
         self.api_creds = Some(api_creds);
+        self.extra_api_creds.clear();
+        Ok(())
+    }
+
+    /// Spreads L2 requests over several API keys, picking them round robin, to
+    /// raise the effective rate limit. Every key must have been created by this
+    /// client's signer, since L2 headers carry its address. The first key is the
+    /// primary one: API key management (listing, validating, deleting) always
+    /// uses it.
+    pub fn set_api_creds_pool(&mut self, pool: Vec<ApiCreds>) -> ClientResult<()> {
+        for creds in &pool {
+            creds.validate()?;
+        }
+        let mut pool = pool.into_iter();
+        self.api_creds = Some(pool.next().context("API credentials pool is empty")?);
+        self.extra_api_creds = pool.collect();
+        Ok(())
+    }
+
+    pub fn add_rounding_config(&mut self, tick_size: Decimal, round_config: RoundConfig) {
+        self.order_builder
+            .as_mut()
+            .expect("OrderBuilder not set")
+            .add_rounding_config(tick_size, round_config);
+    }
+
+    /// Switches the maker address, e.g. to trade through a proxy wallet. Only
+    /// orders signed afterwards are affected; recreate any order that was built
+    /// but not yet posted.
+    pub fn set_funder(&mut self, funder: Address) {
+        self.order_builder
+            .as_mut()
+            .expect("OrderBuilder not set")
+            .set_funder(funder);
+    }
+
+    /// Switches the signature type for orders signed afterwards. See [`ClobClient::set_funder`].
+    pub fn set_signature_type(&mut self, sig_type: SigType) {
+        self.order_builder
+            .as_mut()
+            .expect("OrderBuilder not set")
+            .set_sig_type(sig_type);
+    }
+
+    pub fn funder(&self) -> Option<Address> {
+        Some(self.order_builder.as_ref()?.funder())
+    }
+
+    pub fn signature_type(&self) -> Option<SigType> {
+        Some(self.order_builder.as_ref()?.sig_type())
+    }
+
+    /// Checks that a proxy or safe signature type comes with a funder other than
+    /// the signer. Meant as a startup self check; orders are built regardless.
+    pub fn check_signer_config(&self) -> ClientResult<()> {
+        self.order_builder
+            .as_ref()
+            .expect("OrderBuilder not set")
+            .check_funder()
     }
 
     #[inline]
@@ -152,13 +452,55 @@ impl ClobClient {
         (signer, chain_id)
     }
 
+    /// Signer and the primary API credentials, ignoring any pool.
     #[inline]
-    fn get_l2_parameters(&self) -> (&impl EthSigner, &ApiCreds) {
+    fn get_primary_l2_parameters(&self) -> ClientResult<(&impl EthSigner, &ApiCreds)> {
+        self.check_clock_skew()?;
         let signer = self.signer.as_ref().expect("Signer is not set");
-        (
-            signer,
-            self.api_creds.as_ref().expect("API credentials not set."),
-        )
+        let primary = self.api_creds.as_ref().expect("API credentials not set.");
+        Ok((signer, primary))
+    }
+
+    #[inline]
+    fn get_l2_parameters(&self) -> ClientResult<(&impl EthSigner, &ApiCreds)> {
+        let (signer, primary) = self.get_primary_l2_parameters()?;
+        if self.extra_api_creds.is_empty() {
+            return Ok((signer, primary));
+        }
+        let idx =
+            self.next_api_creds.fetch_add(1, Ordering::Relaxed) % (self.extra_api_creds.len() + 1);
+        let creds = match idx {
+            0 => primary,
+            i => &self.extra_api_creds[i - 1],
+        };
+        Ok((signer, creds))
+    }
+
+    /// Fails if the last measured `clock_skew` is beyond `max_clock_skew`, since
+    /// the server rejects L2 headers whose timestamp is too far off its own.
+    fn check_clock_skew(&self) -> ClientResult<()> {
+        let (Some(max), Some(skew)) = (self.max_clock_skew, self.cached_clock_skew()) else {
+            return Ok(());
+        };
+        if skew.unsigned_abs() > max.as_secs() {
+            let direction = if skew > 0 { "behind" } else { "ahead of" };
+            return Err(anyhow!(
+                "Local clock is {}s {direction} the server, more than the allowed {}s. \
+                 Fix the system clock before sending authenticated requests",
+                skew.unsigned_abs(),
+                max.as_secs()
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn has_signer(&self) -> bool {
+        self.signer.is_some()
+    }
+
+    /// Whether L2 authenticated endpoints, such as posting orders, can be used.
+    pub fn has_api_creds(&self) -> bool {
+        self.signer.is_some() && self.api_creds.is_some()
     }
 
     pub fn get_address(&self) -> Option<String> {
@@ -177,37 +519,196 @@ impl ClobClient {
         Some(get_contract_config(self.chain_id?, false)?.exchange)
     }
 
+    /// Builds the on-chain call that invalidates every order signed with the
+    /// current nonce. The CLOB has no endpoint for this, so the returned call
+    /// must be sent from the signer's address by the caller's own provider.
+    pub fn invalidate_nonce_call(&self, neg_risk: bool) -> ClientResult<ContractCall> {
+        let chain_id = self.chain_id.context("Chain id is not set")?;
+        increment_nonce_call(chain_id, neg_risk)
+    }
+
     fn create_request_with_headers(
         &self,
         method: Method,
         endpoint: &str,
         headers: impl Iterator<Item = (&'static str, String)>,
+    ) -> RequestBuilder {
+        let host = format!("{}{}", self.host, self.base_path);
+        self.create_host_request_with_headers(&host, method, endpoint, headers)
+    }
+
+    fn create_host_request_with_headers(
+        &self,
+        host: &str,
+        method: Method,
+        endpoint: &str,
+        headers: impl Iterator<Item = (&'static str, String)>,
     ) -> RequestBuilder {
         let req = self
             .http_client
-            .request(method, format!("{}{endpoint}", &self.host));
+            .request(method, format!("{host}{endpoint}"));
 
         headers.fold(req, |r, (k, v)| r.header(HeaderName::from_static(k), v))
     }
 
+    /// L2 authenticated request against the data API. The HMAC only covers the
+    /// path, so the same headers are valid on either host.
+    fn create_data_api_l2_request(
+        &self,
+        method: Method,
+        endpoint: &str,
+    ) -> ClientResult<RequestBuilder> {
+        let (signer, creds) = self.get_l2_parameters()?;
+        let (headers, _) =
+            create_l2_headers::<Value>(signer, creds, method.as_str(), endpoint, None)?;
+
+        Ok(self.create_host_request_with_headers(
+            self.data_api_host(),
+            method,
+            endpoint,
+            headers.into_iter(),
+        ))
+    }
+
+    async fn send(&self, req: RequestBuilder) -> ClientResult<Response> {
+        let mut req = req.build()?;
+        let _in_flight = self.in_flight.read().await;
+        if self.is_shut_down() {
+            return Err(anyhow!(
+                "Client is shut down, not sending {} {}",
+                req.method(),
+                req.url().path()
+            ));
+        }
+        for m in &self.middleware {
+            m.on_request(&mut req);
+        }
+        let permit = match &self.request_permits {
+            Some(permits) => Some(permits.acquire().await?),
+            None => None,
+        };
+        let resp = match &self.transport {
+            Some(t) => t.send(req).await?,
+            None => self.http_client.execute(req).await?,
+        };
+        // With a request limit the body is read while the permit is held, so
+        // the limit covers the download and not just the headers.
+        let max = self.http_config.max_response_bytes;
+        let mut resp = match (max, &permit) {
+            (None, None) => resp,
+            _ => buffer_response_body(resp, max).await?,
+        };
+        drop(permit);
+        for m in &self.middleware {
+            m.on_response(&mut resp);
+        }
+        Ok(resp)
+    }
+
+    async fn send_json<T: DeserializeOwned>(&self, req: RequestBuilder) -> ClientResult<T> {
+        decode_json(self.send(req).await?).await
+    }
+
     pub async fn get_ok(&self) -> bool {
-        self.http_client
-            .get(format!("{}/", &self.host))
-            .send()
+        let req = self.http_client.get(self.endpoint_url("/"));
+        self.send(req)
             .await
-            .is_ok()
+            .is_ok_and(|resp| resp.status().is_success())
+    }
+
+    /// Whether the exchange is currently accepting orders. The CLOB has no pause
+    /// status endpoint, so this probes the `/` health check: it answers 503
+    /// during maintenance and 425 while the matching engine restarts, both
+    /// reported as `false`. Other failures are returned as errors.
+    pub async fn is_exchange_operational(&self) -> ClientResult<bool> {
+        let req = self.http_client.get(self.endpoint_url("/"));
+        let resp = self.send(req).await?;
+        match resp.status() {
+            StatusCode::SERVICE_UNAVAILABLE | StatusCode::TOO_EARLY => Ok(false),
+            _ => check_status(resp).await.map(|_| true),
+        }
+    }
+
+    /// Sends an authenticated request to an endpoint this client has no wrapper
+    /// for. L2 headers are used when API credentials are set, L1 headers
+    /// otherwise. The response is returned untouched, without a status check.
+    pub async fn authed_request<T>(
+        &self,
+        method: Method,
+        endpoint: &str,
+        body: Option<&T>,
+    ) -> ClientResult<Response>
+    where
+        T: ?Sized + Serialize,
+    {
+        let req = self.authed_request_builder(method, endpoint, body)?;
+        self.send(req).await
+    }
+
+    /// `GET` counterpart of [`ClobClient::authed_request`]. `query` is not part
+    /// of the signed path.
+    pub async fn authed_get(
+        &self,
+        endpoint: &str,
+        query: &[(&str, &str)],
+    ) -> ClientResult<Response> {
+        let req = self
+            .authed_request_builder::<Value>(Method::GET, endpoint, None)?
+            .query(query);
+        self.send(req).await
+    }
+
+    fn authed_request_builder<T>(
+        &self,
+        method: Method,
+        endpoint: &str,
+        body: Option<&T>,
+    ) -> ClientResult<RequestBuilder>
+    where
+        T: ?Sized + Serialize,
+    {
+        let (headers, body_str) = if self.api_creds.is_some() {
+            let (signer, creds) = self.get_l2_parameters()?;
+            create_l2_headers(signer, creds, method.as_str(), endpoint, body)?
+        } else {
+            let (signer, _) = self.get_l1_parameters();
+            let body_str = body.map(serde_json::to_string).transpose()?;
+            (create_l1_headers(signer, None)?, body_str)
+        };
+
+        let req = self.create_request_with_headers(method, endpoint, headers.into_iter());
+        Ok(match body_str {
+            Some(body) => req
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body),
+            None => req,
+        })
     }
 
     pub async fn get_server_time(&self) -> ClientResult<u64> {
-        let resp = self
-            .http_client
-            .get(format!("{}/time", &self.host))
-            .send()
-            .await?
-            .text()
-            .await?
-            .parse::<u64>()?;
-        Ok(resp)
+        let req = self.http_client.get(self.endpoint_url("/time"));
+        let resp = check_status(self.send(req).await?).await?;
+        Ok(resp.text().await?.parse::<u64>()?)
+    }
+
+    /// Server time minus local time in seconds, measured now and remembered for
+    /// the `with_max_clock_skew` check. Positive means the local clock is behind.
+    pub async fn clock_skew(&self) -> ClientResult<i64> {
+        let server = self.get_server_time().await? as i64;
+        let skew = server - get_current_unix_time_secs() as i64;
+        *self.clock_skew.lock().unwrap() = Some(skew);
+        Ok(skew)
+    }
+
+    /// Local time corrected by the last measured `clock_skew`, in milliseconds.
+    fn server_time_millis(&self) -> u64 {
+        let skew_ms = self.cached_clock_skew().unwrap_or(0) * 1000;
+        get_current_unix_time_millis().saturating_add_signed(skew_ms)
+    }
+
+    /// Result of the last `clock_skew` call, if any.
+    pub fn cached_clock_skew(&self) -> Option<i64> {
+        *self.clock_skew.lock().unwrap()
     }
 
     pub async fn create_api_key(&self, nonce: Option<U256>) -> ClientResult<ApiCreds> {
@@ -218,7 +719,7 @@ impl ClobClient {
 
         let req = self.create_request_with_headers(method, endpoint, headers.into_iter());
 
-        Ok(req.send().await?.json::<ApiCreds>().await?)
+        self.send_json::<ApiCreds>(req).await
     }
 
     pub async fn derive_api_key(&self, nonce: Option<U256>) -> ClientResult<ApiCreds> {
@@ -229,7 +730,7 @@ impl ClobClient {
 
         let req = self.create_request_with_headers(method, endpoint, headers.into_iter());
 
-        Ok(req.send().await?.json::<ApiCreds>().await?)
+        self.send_json::<ApiCreds>(req).await
     }
 
     pub async fn create_or_derive_api_key(&self, nonce: Option<U256>) -> ClientResult<ApiCreds> {
@@ -240,38 +741,85 @@ impl ClobClient {
         creds
     }
 
+    /// Like [`ClobClient::create_or_derive_api_key`], but first tries the
+    /// credentials cached at `path` and keeps them if they still authenticate.
+    /// Freshly derived credentials are written back to `path` as JSON, passed
+    /// through `cipher` when one is given. Either way the credentials are set on
+    /// the client. Use a separate path per signer and nonce.
+    pub async fn load_or_derive_api_key(
+        &mut self,
+        nonce: Option<U256>,
+        path: impl AsRef<Path>,
+        cipher: Option<&dyn CredsCipher>,
+    ) -> ClientResult<ApiCreds> {
+        let path = path.as_ref();
+        if let Some(creds) = load_creds(path, cipher) {
+            if self.set_api_creds(creds.clone()).is_ok() && self.validate_api_creds().await.is_ok()
+            {
+                return Ok(creds);
+            }
+            self.api_creds = None;
+            self.extra_api_creds.clear();
+        }
+
+        let creds = self.create_or_derive_api_key(nonce).await?;
+        self.set_api_creds(creds.clone())?;
+        store_creds(path, &creds, cipher)?;
+        Ok(creds)
+    }
+
     pub async fn get_api_keys(&self) -> ClientResult<Vec<String>> {
         let method = Method::GET;
         let endpoint = "/auth/api-keys";
-        let (signer, creds) = self.get_l2_parameters();
+        let (signer, creds) = self.get_primary_l2_parameters()?;
         let (headers, _) =
             create_l2_headers::<Value>(signer, creds, method.as_str(), endpoint, None)?;
 
         let req = self.create_request_with_headers(method, endpoint, headers.into_iter());
 
-        Ok(req.send().await?.json::<ApiKeysResponse>().await?.api_keys)
+        Ok(self.send_json::<ApiKeysResponse>(req).await?.api_keys)
+    }
+
+    /// Checks that the configured API credentials still authenticate, without
+    /// touching any orders. Meant as a startup self check.
+    pub async fn validate_api_creds(&self) -> ClientResult<()> {
+        let method = Method::GET;
+        let endpoint = "/auth/api-keys";
+        let (signer, creds) = self.get_primary_l2_parameters()?;
+        let (headers, _) =
+            create_l2_headers::<Value>(signer, creds, method.as_str(), endpoint, None)?;
+
+        let req = self.create_request_with_headers(method, endpoint, headers.into_iter());
+        let resp = self.send(req).await?;
+        let status = resp.status();
+        if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+            return Err(anyhow!(
+                "API key {} was rejected with status {status}, it may have been revoked",
+                creds.api_key
+            ));
+        }
+        check_status(resp).await?;
+        Ok(())
     }
 
     pub async fn delete_api_key(&self) -> ClientResult<String> {
         let method = Method::DELETE;
         let endpoint = "/auth/api-key";
-        let (signer, creds) = self.get_l2_parameters();
+        let (signer, creds) = self.get_primary_l2_parameters()?;
         let (headers, _) =
             create_l2_headers::<Value>(signer, creds, method.as_str(), endpoint, None)?;
         let req = self.create_request_with_headers(method, endpoint, headers.into_iter());
 
-        Ok(req.send().await?.text().await?)
+        Ok(self.send(req).await?.text().await?)
     }
 
     pub async fn get_midpoint(&self, token_id: &str) -> ClientResult<MidpointResponse> {
-        Ok(self
+        let req = self
             .http_client
-            .get(format!("{}/midpoint", &self.host))
-            .query(&[("token_id", token_id)])
-            .send()
-            .await?
-            .json::<MidpointResponse>()
-            .await?)
+            .get(self.endpoint_url("/midpoint"))
+            .query(&[("token_id", token_id)]);
+
+        self.send_json::<MidpointResponse>(req).await
     }
 
     pub async fn get_midpoints(
@@ -283,26 +831,44 @@ impl ClobClient {
             .map(|b| HashMap::from([("token_id", b.clone())]))
             .collect::<Vec<HashMap<&str, String>>>();
 
-        Ok(self
+        let req = self
             .http_client
-            .post(format!("{}/midpoints", &self.host))
-            .json(&v)
-            .send()
-            .await?
-            .json::<HashMap<String, Decimal>>()
-            .await?)
+            .post(self.endpoint_url("/midpoints"))
+            .json(&v);
+
+        self.send_json::<HashMap<String, Decimal>>(req).await
+    }
+
+    /// Midpoints in the order of `token_ids`, with `None` for tokens the server
+    /// did not price.
+    pub async fn get_midpoints_ordered(
+        &self,
+        token_ids: &[String],
+    ) -> ClientResult<Vec<(String, Option<Decimal>)>> {
+        let midpoints = self.get_midpoints(token_ids).await?;
+        Ok(align_to_token_ids(token_ids, midpoints))
+    }
+
+    /// How far the midpoints of `token_a` and `token_b` sum above (positive) or
+    /// below (negative) 1. For the two outcomes of one market this should be
+    /// close to zero; anything else is a mispricing.
+    pub async fn arbitrage_gap(&self, token_a: &str, token_b: &str) -> ClientResult<Decimal> {
+        let ids = [token_a.to_owned(), token_b.to_owned()];
+        let mut sum = Decimal::ZERO;
+        for (id, mid) in self.get_midpoints_ordered(&ids).await? {
+            sum += mid.with_context(|| format!("No midpoint for token {id}"))?;
+        }
+        Ok(sum - Decimal::ONE)
     }
 
     pub async fn get_price(&self, token_id: &str, side: Side) -> ClientResult<PriceResponse> {
-        Ok(self
+        let req = self
             .http_client
-            .get(format!("{}/price", &self.host))
+            .get(self.endpoint_url("/price"))
             .query(&[("token_id", token_id)])
-            .query(&[("side", side.as_str())])
-            .send()
-            .await?
-            .json::<PriceResponse>()
-            .await?)
+            .query(&[("side", side.as_str())]);
+
+        self.send_json::<PriceResponse>(req).await
     }
     pub async fn get_prices(
         &self,
@@ -318,25 +884,19 @@ impl ClobClient {
             })
             .collect::<Vec<HashMap<&str, String>>>();
 
-        Ok(self
-            .http_client
-            .post(format!("{}/prices", &self.host))
-            .json(&v)
-            .send()
-            .await?
-            .json::<HashMap<String, HashMap<Side, Decimal>>>()
-            .await?)
+        let req = self.http_client.post(self.endpoint_url("/prices")).json(&v);
+
+        self.send_json::<HashMap<String, HashMap<Side, Decimal>>>(req)
+            .await
     }
 
     pub async fn get_spread(&self, token_id: &str) -> ClientResult<SpreadResponse> {
-        Ok(self
+        let req = self
             .http_client
-            .get(format!("{}/spread", &self.host))
-            .query(&[("token_id", token_id)])
-            .send()
-            .await?
-            .json::<SpreadResponse>()
-            .await?)
+            .get(self.endpoint_url("/spread"))
+            .query(&[("token_id", token_id)]);
+
+        self.send_json::<SpreadResponse>(req).await
     }
 
     pub async fn get_spreads(
@@ -348,39 +908,139 @@ impl ClobClient {
             .map(|b| HashMap::from([("token_id", b.clone())]))
             .collect::<Vec<HashMap<&str, String>>>();
 
-        Ok(self
+        let req = self
             .http_client
-            .post(format!("{}/spreads", &self.host))
-            .json(&v)
-            .send()
-            .await?
-            .json::<HashMap<String, Decimal>>()
-            .await?)
+            .post(self.endpoint_url("/spreads"))
+            .json(&v);
+
+        self.send_json::<HashMap<String, Decimal>>(req).await
+    }
+
+    /// Spreads in the order of `token_ids`, with `None` for tokens the server
+    /// did not return.
+    pub async fn get_spreads_ordered(
+        &self,
+        token_ids: &[String],
+    ) -> ClientResult<Vec<(String, Option<Decimal>)>> {
+        let spreads = self.get_spreads(token_ids).await?;
+        Ok(align_to_token_ids(token_ids, spreads))
     }
 
-    // cache
     pub async fn get_tick_size(&self, token_id: &str) -> ClientResult<Decimal> {
-        Ok(self
-            .http_client
-            .get(format!("{}/tick-size", &self.host))
-            .query(&[("token_id", token_id)])
-            .send()
-            .await?
-            .json::<TickSizeResponse>()
-            .await?
-            .minimum_tick_size)
+        self.tick_sizes
+            .get_or_fetch(token_id, || async {
+                let req = self
+                    .http_client
+                    .get(self.endpoint_url("/tick-size"))
+                    .query(&[("token_id", token_id)]);
+
+                Ok(self
+                    .send_json::<TickSizeResponse>(req)
+                    .await?
+                    .minimum_tick_size)
+            })
+            .await
     }
-    // Cache
+
     pub async fn get_neg_risk(&self, token_id: &str) -> ClientResult<bool> {
-        Ok(self
-            .http_client
-            .get(format!("{}/neg-risk", &self.host))
-            .query(&[("token_id", token_id)])
-            .send()
-            .await?
-            .json::<NegRiskResponse>()
-            .await?
-            .neg_risk)
+        self.neg_risks
+            .get_or_fetch(token_id, || async {
+                let req = self
+                    .http_client
+                    .get(self.endpoint_url("/neg-risk"))
+                    .query(&[("token_id", token_id)]);
+
+                Ok(self.send_json::<NegRiskResponse>(req).await?.neg_risk)
+            })
+            .await
+    }
+
+    /// Tick size, minimum order size and neg risk of a token. The order book
+    /// usually carries all three, so this is a single request; missing fields
+    /// fall back to their own endpoints. Populates the tick size and neg risk caches.
+    pub async fn get_order_constraints(&self, token_id: &str) -> ClientResult<OrderConstraints> {
+        let book = self.get_order_book(token_id).await?;
+
+        let min_tick_size = self.book_tick_size(token_id, &book).await?;
+        let neg_risk = match book.neg_risk {
+            Some(neg_risk) => {
+                self.neg_risks.insert(token_id, neg_risk);
+                neg_risk
+            }
+            None => self.get_neg_risk(token_id).await?,
+        };
+        let min_order_size = match book.min_order_size {
+            Some(size) => size,
+            None => self.get_market(&book.market).await?.minimum_order_size,
+        };
+
+        Ok(OrderConstraints {
+            min_tick_size,
+            min_order_size,
+            neg_risk,
+        })
+    }
+
+    /// Tick size carried by `book`, caching it, or the cached/fetched one when
+    /// the book leaves it out. Fails rather than handing out a tick size that
+    /// isn't positive.
+    async fn book_tick_size(
+        &self,
+        token_id: &str,
+        book: &OrderBookSummary,
+    ) -> ClientResult<Decimal> {
+        let tick_size = match book.tick_size {
+            Some(tick_size) => {
+                check_tick_size(tick_size)?;
+                self.tick_sizes.insert(token_id, tick_size);
+                tick_size
+            }
+            None => self.get_tick_size(token_id).await?,
+        };
+        check_tick_size(tick_size)?;
+        Ok(tick_size)
+    }
+
+    /// Shared handle to the tick size cache, e.g. for invalidation on market updates.
+    pub fn tick_size_cache(&self) -> Arc<TokenCache<Decimal>> {
+        self.tick_sizes.clone()
+    }
+
+    /// Shared handle to the neg risk cache.
+    pub fn neg_risk_cache(&self) -> Arc<TokenCache<bool>> {
+        self.neg_risks.clone()
+    }
+
+    /// Shared handle to the fee rate cache.
+    pub fn fee_rate_cache(&self) -> Arc<TokenCache<u32>> {
+        self.fee_rates.clone()
+    }
+
+    pub async fn get_fee_rate_bps(&self, token_id: &str) -> ClientResult<u32> {
+        self.fee_rates
+            .get_or_fetch(token_id, || async {
+                let req = self
+                    .http_client
+                    .get(self.endpoint_url("/fee-rate"))
+                    .query(&[("token_id", token_id)]);
+
+                Ok(self.send_json::<FeeRateResponse>(req).await?.base_fee)
+            })
+            .await
+    }
+
+    /// Uses the given extras, filling in the token's cached fee rate unless the
+    /// caller set one.
+    async fn resolve_extras(
+        &self,
+        token_id: &str,
+        extras: Option<ExtraOrderArgs>,
+    ) -> ClientResult<ExtraOrderArgs> {
+        let mut extras = extras.unwrap_or_default();
+        if extras.fee_rate_bps.is_none() {
+            extras.fee_rate_bps = Some(self.get_fee_rate_bps(token_id).await?);
+        }
+        Ok(extras)
     }
 
     async fn resolve_tick_size(
@@ -393,13 +1053,16 @@ impl ClobClient {
             .await
             .context("Error fetching tick size")?;
 
+        // Equal tick sizes may differ in scale (0.01 vs 0.0100). Lookups keyed by
+        // tick size already ignore scale; this keeps it out of filled options and
+        // error messages.
         match tick_size {
-            None => Ok(min_tick_size),
+            None => Ok(min_tick_size.normalize()),
             Some(t) => {
                 if t < min_tick_size {
                     Err(anyhow!("Tick size {t} is smaller than min_tick_size {min_tick_size} for token_id: {token_id}"))
                 } else {
-                    Ok(t)
+                    Ok(t.normalize())
                 }
             }
         }
@@ -410,10 +1073,8 @@ impl ClobClient {
         token_id: &str,
         options: Option<&CreateOrderOptions>,
     ) -> ClientResult<CreateOrderOptions> {
-        let (tick_size, neg_risk) = match options {
-            Some(o) => (o.tick_size, o.neg_risk),
-            None => (None, None),
-        };
+        let options = options.cloned().unwrap_or_default();
+        let (tick_size, neg_risk) = (options.tick_size, options.neg_risk);
 
         let tick_size = self.resolve_tick_size(token_id, tick_size).await?;
 
@@ -422,65 +1083,110 @@ impl ClobClient {
             None => self.get_neg_risk(token_id).await?,
         };
 
+        let min_order_size = match options.min_order_size {
+            None if options.max_size_rounding.is_some() => Some(
+                self.get_order_constraints(token_id)
+                    .await
+                    .context("Error fetching minimum order size")?
+                    .min_order_size,
+            ),
+            min_order_size => min_order_size,
+        };
+
         Ok(CreateOrderOptions {
             neg_risk: Some(neg_risk),
             tick_size: Some(tick_size),
+            min_order_size,
+            ..options
         })
     }
 
-    fn is_price_in_range(&self, price: Decimal, tick_size: Decimal) -> bool {
-        let min_price = tick_size;
-        let max_price = Decimal::ONE - tick_size;
+    pub async fn create_order(
+        &self,
+        order_args: &OrderArgs,
+        expiration: Option<Expiration>,
+        extras: Option<ExtraOrderArgs>,
+        options: Option<&CreateOrderOptions>,
+    ) -> ClientResult<SignedOrderRequest> {
+        let (chain_id, expiration, extras, options) = self
+            .prepare_limit_order(order_args, expiration, extras, options)
+            .await?;
 
-        if price < min_price || price > max_price {
-            return false;
-        }
-        true
+        self.order_builder
+            .as_ref()
+            .expect("OrderBuilder not set")
+            .create_order(chain_id, order_args, expiration, &extras, options)
     }
 
-    pub async fn create_order(
+    /// Builds an order like [`ClobClient::create_order`] without signing it, for
+    /// setups where a separate service holds the key. Pass the returned digest's
+    /// signature to [`finalize_order`] to get the postable request.
+    pub async fn build_unsigned_order(
         &self,
         order_args: &OrderArgs,
-        expiration: Option<u64>,
+        expiration: Option<Expiration>,
         extras: Option<ExtraOrderArgs>,
         options: Option<&CreateOrderOptions>,
-    ) -> ClientResult<SignedOrderRequest> {
+    ) -> ClientResult<UnsignedOrder> {
+        let (chain_id, expiration, extras, options) = self
+            .prepare_limit_order(order_args, expiration, extras, options)
+            .await?;
+
+        self.order_builder
+            .as_ref()
+            .expect("OrderBuilder not set")
+            .build_unsigned_order(chain_id, order_args, expiration, &extras, options)
+    }
+
+    async fn prepare_limit_order(
+        &self,
+        order_args: &OrderArgs,
+        expiration: Option<Expiration>,
+        extras: Option<ExtraOrderArgs>,
+        options: Option<&CreateOrderOptions>,
+    ) -> ClientResult<(u64, u64, ExtraOrderArgs, CreateOrderOptions)> {
         let (_, chain_id) = self.get_l1_parameters();
+        check_chain_supported(chain_id)?;
+        if options.is_some_and(|o| o.check_accepting_order_time) {
+            self.check_accepting_order_time(&order_args.token_id)
+                .await?;
+        }
+        if let Some(max_bps) = options.and_then(|o| o.max_price_through_book_bps) {
+            let book = self.get_order_book(&order_args.token_id).await?;
+            check_price_through_book(order_args, &book, max_bps)?;
+        }
 
         let create_order_options = self
             .get_filled_order_options(order_args.token_id.as_ref(), options)
             .await?;
-        let expiration = expiration.unwrap_or(0);
-        let extras = extras.unwrap_or_default();
+        let expiration = expiration.unwrap_or_default().to_timestamp();
+        let extras = self
+            .resolve_extras(order_args.token_id.as_ref(), extras)
+            .await?;
 
-        if !self.is_price_in_range(
-            order_args.price,
-            create_order_options.tick_size.expect("Should be filled"),
-        ) {
-            return Err(anyhow!("Price is not in range of tick_size"));
+        if !create_order_options.skip_price_validation {
+            check_price_in_range(
+                order_args.price,
+                create_order_options.tick_size.expect("Should be filled"),
+            )?;
         }
 
-        self.order_builder
-            .as_ref()
-            .expect("OrderBuilder not set")
-            .create_order(
-                chain_id,
-                order_args,
-                expiration,
-                &extras,
-                create_order_options,
-            )
+        Ok((chain_id, expiration, extras, create_order_options))
     }
 
     pub async fn get_order_book(&self, token_id: &str) -> ClientResult<OrderBookSummary> {
-        Ok(self
+        let req = self
             .http_client
-            .get(format!("{}/book", &self.host))
-            .query(&[("token_id", token_id)])
-            .send()
-            .await?
-            .json::<OrderBookSummary>()
-            .await?)
+            .get(self.endpoint_url("/book"))
+            .query(&[("token_id", token_id)]);
+
+        self.send_json::<OrderBookSummary>(req).await
+    }
+
+    /// Best bid and ask of `token_id`. The CLOB has no top of book endpoint
+    /// that includes sizes, so this still downloads the full book.
+    pub async fn get_top_of_book(&self, token_id: &str) -> ClientResult<TopOfBook> {
+        Ok(self.get_order_book(token_id).await?.top_of_book())
     }
 
     pub async fn get_order_books(
@@ -492,31 +1198,137 @@ impl ClobClient {
             .map(|b| HashMap::from([("token_id", b.clone())]))
             .collect::<Vec<HashMap<&str, String>>>();
 
-        Ok(self
-            .http_client
-            .post(format!("{}/books", &self.host))
-            .json(&v)
-            .send()
-            .await?
-            .json::<Vec<OrderBookSummary>>()
-            .await?)
+        let req = self.http_client.post(self.endpoint_url("/books")).json(&v);
+
+        self.send_json::<Vec<OrderBookSummary>>(req).await
     }
 
-    async fn calculate_market_price(
+    /// Like the price lookup done by `create_market_order`, but reports how much
+    /// of `amount` the book can fill instead of erroring on thin liquidity.
+    pub async fn try_calculate_market_price(
         &self,
         token_id: &str,
         side: Side,
         amount: Decimal,
-    ) -> ClientResult<Decimal> {
+    ) -> ClientResult<MarketPriceFill> {
+        let book = self.get_order_book(token_id).await?;
+        let ob = self
+            .order_builder
+            .as_ref()
+            .expect("No orderBuilder set for client!");
+        Ok(match side {
+            Side::BUY => ob.try_calculate_market_price(&book.asks, side, amount),
+            Side::SELL => ob.try_calculate_market_price(&book.bids, side, amount),
+        })
+    }
+
+    /// Args for an order of `size` joining the best price on `side` of the
+    /// current book (the best bid for BUY, the best ask for SELL), on the tick grid.
+    pub async fn join_best(
+        &self,
+        token_id: &str,
+        side: Side,
+        size: Decimal,
+    ) -> ClientResult<OrderArgs> {
+        let book = self.get_order_book(token_id).await?;
+        let best = match side {
+            Side::BUY => book.best_bid(),
+            Side::SELL => book.best_ask(),
+        }
+        .with_context(|| {
+            format!(
+                "No {} orders in the book for token {token_id}",
+                side.as_str()
+            )
+        })?;
+        let tick_size = self.book_tick_size(token_id, &book).await?;
+        let price = snap_to_tick(best.price, tick_size, side)?;
+        Ok(OrderArgs::new(token_id, price, size, side))
+    }
+
+    /// Snaps every order's price onto its token's tick grid in place, the same
+    /// way as [`snap_to_tick`], fetching each token's tick size once. Orders whose
+    /// snapped price falls outside the valid range are left untouched and
+    /// returned by index instead of being clamped.
+    pub async fn snap_orders(
+        &self,
+        orders: &mut [OrderArgs],
+    ) -> ClientResult<Vec<(usize, PriceOutOfRange)>> {
+        let mut tick_sizes = HashMap::new();
+        for order in orders.iter() {
+            if !tick_sizes.contains_key(&order.token_id) {
+                let tick_size = self.get_tick_size(&order.token_id).await?;
+                check_tick_size(tick_size)?;
+                tick_sizes.insert(order.token_id.clone(), tick_size);
+            }
+        }
+
+        let mut rejected = Vec::new();
+        for (idx, order) in orders.iter_mut().enumerate() {
+            let tick_size = tick_sizes[&order.token_id];
+            let price = round_to_tick(order.price, tick_size, order.side);
+            match check_price_in_range(price, tick_size) {
+                Ok(()) => order.price = price,
+                Err(e) => rejected.push((idx, e)),
+            }
+        }
+        Ok(rejected)
+    }
+
+    /// Smallest price that would make a new order the best on `side`: one tick
+    /// above the best bid for `BUY`, one below the best ask for `SELL`. `None`
+    /// when that is out of the valid price range or would cross the spread.
+    pub async fn improve_price(&self, token_id: &str, side: Side) -> ClientResult<Option<Decimal>> {
+        let book = self.get_order_book(token_id).await?;
+        let (best, opposite) = match side {
+            Side::BUY => (book.best_bid(), book.best_ask()),
+            Side::SELL => (book.best_ask(), book.best_bid()),
+        };
+        let best = best.with_context(|| {
+            format!(
+                "No {} orders in the book for token {token_id}",
+                side.as_str()
+            )
+        })?;
+        let tick_size = self.book_tick_size(token_id, &book).await?;
+        Ok(improved_price(
+            best.price,
+            opposite.map(|o| o.price),
+            tick_size,
+            side,
+        ))
+    }
+
+    /// Worst price needed to fully fill `size` shares on `side` against the
+    /// current book, or `None` if there isn't enough depth.
+    pub async fn executable_price(
+        &self,
+        token_id: &str,
+        side: Side,
+        size: Decimal,
+    ) -> ClientResult<Option<Decimal>> {
+        let book = self.get_order_book(token_id).await?;
+        Ok(match side {
+            Side::BUY => executable_price(&book.asks, side, size),
+            Side::SELL => executable_price(&book.bids, side, size),
+        })
+    }
+
+    /// Volume weighted average price of spending (or, for `SELL`, receiving)
+    /// `notional` USDC against the current book, or `None` if there isn't enough
+    /// depth. Unlike [`ClobClient::executable_price`] this is the average over
+    /// every level touched, not the worst one.
+    pub async fn vwap(
+        &self,
+        token_id: &str,
+        side: Side,
+        notional: Decimal,
+    ) -> ClientResult<Option<Decimal>> {
         let book = self.get_order_book(token_id).await?;
-        let ob = self
-            .order_builder
-            .as_ref()
-            .expect("No orderBuilder set for client!");
-        match side {
-            Side::BUY => ob.calculate_market_price(&book.asks, amount),
-            Side::SELL => ob.calculate_market_price(&book.bids, amount),
-        }
+        Ok(match side {
+            Side::BUY => vwap(&book.asks, side, notional),
+            Side::SELL => vwap(&book.bids, side, notional),
+        })
     }
 
     pub async fn create_market_order(
@@ -526,26 +1338,36 @@ impl ClobClient {
         options: Option<&CreateOrderOptions>,
     ) -> ClientResult<SignedOrderRequest> {
         let (_, chain_id) = self.get_l1_parameters();
+        check_chain_supported(chain_id)?;
 
         let create_order_options = self
             .get_filled_order_options(order_args.token_id.as_ref(), options)
             .await?;
 
-        let extras = extras.unwrap_or_default();
-        let price = self
-            .calculate_market_price(&order_args.token_id, Side::BUY, order_args.amount)
+        let extras = self
+            .resolve_extras(order_args.token_id.as_ref(), extras)
             .await?;
-        if !self.is_price_in_range(
+        let book = self.get_order_book(&order_args.token_id).await?;
+        check_book_age(
+            book.timestamp,
+            self.server_time_millis(),
+            &create_order_options,
+        )?;
+        let ob = self.order_builder.as_ref().expect("OrderBuilder not set");
+        let price = ob.calculate_market_price(&book.asks, order_args.amount)?;
+        check_market_order_liquidity(
+            &book.asks,
+            order_args.amount,
+            price,
+            book.midpoint(),
+            &create_order_options,
+        )?;
+        check_price_in_range(
             price,
             create_order_options.tick_size.expect("Should be filled"),
-        ) {
-            return Err(anyhow!("Price is not in range of tick_size"));
-        }
+        )?;
 
-        self.order_builder
-            .as_ref()
-            .expect("OrderBuilder not set")
-            .create_market_order(chain_id, order_args, price, &extras, create_order_options)
+        ob.create_market_order(chain_id, order_args, price, &extras, create_order_options)
     }
 
     pub async fn post_order(
@@ -553,7 +1375,7 @@ impl ClobClient {
         order: SignedOrderRequest,
         order_type: OrderType,
     ) -> ClientResult<Value> {
-        let (signer, creds) = self.get_l2_parameters();
+        let (signer, creds) = self.get_l2_parameters()?;
         let body = PostOrder::new(order, creds.api_key.clone(), order_type);
 
         let method = Method::POST;
@@ -567,13 +1389,58 @@ impl ClobClient {
         // body_str is Some because we passed Some(&body)
         let body_str = body_str.expect("body string missing for post_order");
 
-        Ok(req
+        let req = req
             .header(reqwest::header::CONTENT_TYPE, "application/json")
-            .body(body_str)
-            .send()
-            .await?
-            .json::<Value>()
-            .await?)
+            .body(body_str);
+
+        self.send_json::<Value>(req).await
+    }
+
+    /// Posts `body` to `/order` byte for byte, signing exactly that string. Meant
+    /// for debugging signature mismatches against other clients: the caller is
+    /// responsible for `body` being a valid order payload (`order`, `owner`,
+    /// `orderType`), as nothing is re-serialized or checked.
+    pub async fn post_order_raw(&self, body: String) -> ClientResult<Value> {
+        let (signer, creds) = self.get_l2_parameters()?;
+        let method = Method::POST;
+        let endpoint = "/order";
+
+        let headers =
+            create_l2_headers_for_body(signer, creds, method.as_str(), endpoint, Some(&body))?;
+
+        let req = self
+            .create_request_with_headers(method, endpoint, headers.into_iter())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body);
+
+        self.send_json::<Value>(req).await
+    }
+
+    pub async fn post_orders_batch(
+        &self,
+        orders: Vec<(SignedOrderRequest, OrderType)>,
+    ) -> ClientResult<Vec<Value>> {
+        let (signer, creds) = self.get_l2_parameters()?;
+        let body = orders
+            .into_iter()
+            .map(|(order, order_type)| PostOrder::new(order, creds.api_key.clone(), order_type))
+            .collect::<Vec<_>>();
+
+        let method = Method::POST;
+        let endpoint = "/orders";
+
+        let (headers, body_str) =
+            create_l2_headers(signer, creds, method.as_str(), endpoint, Some(&body))?;
+
+        let req = self.create_request_with_headers(method, endpoint, headers.into_iter());
+
+        let body_str = body_str.expect("body string missing for post_orders_batch");
+
+        let req = req
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body_str);
+
+        self.send_json::<Vec<Value>>(req).await
     }
 
     pub async fn create_and_post_order(&self, order_args: &OrderArgs) -> ClientResult<Value> {
@@ -581,8 +1448,8 @@ impl ClobClient {
         self.post_order(order, OrderType::GTC).await
     }
 
-    pub async fn cancel(&self, order_id: &str) -> ClientResult<Value> {
-        let (signer, creds) = self.get_l2_parameters();
+    pub async fn cancel(&self, order_id: &str) -> ClientResult<CancelResponse> {
+        let (signer, creds) = self.get_l2_parameters()?;
         let body = HashMap::from([("orderID", order_id)]);
 
         let method = Method::DELETE;
@@ -595,17 +1462,21 @@ impl ClobClient {
 
         let body_str = body_str.expect("body string missing for cancel");
 
-        Ok(req
+        let req = req
             .header(reqwest::header::CONTENT_TYPE, "application/json")
-            .body(body_str)
-            .send()
-            .await?
-            .json::<Value>()
-            .await?)
+            .body(body_str);
+
+        self.send_json::<CancelResponse>(req).await
+    }
+
+    /// The CLOB uses the order hash as order id, so an order can be canceled with
+    /// just the hash computed locally or returned when posting.
+    pub async fn cancel_by_hash(&self, order_hash: &str) -> ClientResult<CancelResponse> {
+        self.cancel(&normalize_order_hash(order_hash)?).await
     }
 
-    pub async fn cancel_orders(&self, order_ids: &[String]) -> ClientResult<Value> {
-        let (signer, creds) = self.get_l2_parameters();
+    pub async fn cancel_orders(&self, order_ids: &[String]) -> ClientResult<CancelResponse> {
+        let (signer, creds) = self.get_l2_parameters()?;
         let method = Method::DELETE;
         let endpoint = "/orders";
 
@@ -615,17 +1486,33 @@ impl ClobClient {
         let req = self.create_request_with_headers(method, endpoint, headers.into_iter());
         let body_str = body_str.expect("body string missing for cancel_orders");
 
-        Ok(req
+        let req = req
             .header(reqwest::header::CONTENT_TYPE, "application/json")
-            .body(body_str)
-            .send()
+            .body(body_str);
+
+        self.send_json::<CancelResponse>(req).await
+    }
+
+    /// Cancels every open order created more than `max_age_secs` ago. Nothing is
+    /// sent when no order is that old.
+    pub async fn cancel_stale_orders(&self, max_age_secs: u64) -> ClientResult<CancelResponse> {
+        let now = get_current_unix_time_secs();
+        let stale: Vec<String> = self
+            .get_orders(None, None)
             .await?
-            .json::<Value>()
-            .await?)
+            .into_iter()
+            .filter(|o| now.saturating_sub(o.created_at) > max_age_secs)
+            .map(|o| o.id)
+            .collect();
+
+        if stale.is_empty() {
+            return Ok(CancelResponse::default());
+        }
+        self.cancel_orders(&stale).await
     }
 
-    pub async fn cancel_all(&self) -> ClientResult<Value> {
-        let (signer, creds) = self.get_l2_parameters();
+    pub async fn cancel_all(&self) -> ClientResult<CancelResponse> {
+        let (signer, creds) = self.get_l2_parameters()?;
         let method = Method::DELETE;
         let endpoint = "/cancel-all";
 
@@ -634,15 +1521,15 @@ impl ClobClient {
 
         let req = self.create_request_with_headers(method, endpoint, headers.into_iter());
 
-        Ok(req.send().await?.json::<Value>().await?)
+        self.send_json::<CancelResponse>(req).await
     }
 
     pub async fn cancel_market_orders(
         &self,
         market: Option<&str>,
         asset_id: Option<&str>,
-    ) -> ClientResult<Value> {
-        let (signer, creds) = self.get_l2_parameters();
+    ) -> ClientResult<CancelResponse> {
+        let (signer, creds) = self.get_l2_parameters()?;
         let method = Method::DELETE;
         let endpoint = "/cancel-market-orders";
         let body = HashMap::from([
@@ -656,13 +1543,11 @@ impl ClobClient {
         let req = self.create_request_with_headers(method, endpoint, headers.into_iter());
         let body_str = body_str.expect("body string missing for cancel_market_orders");
 
-        Ok(req
+        let req = req
             .header(reqwest::header::CONTENT_TYPE, "application/json")
-            .body(body_str)
-            .send()
-            .await?
-            .json::<Value>()
-            .await?)
+            .body(body_str);
+
+        self.send_json::<CancelResponse>(req).await
     }
 
     pub async fn get_orders(
@@ -670,49 +1555,33 @@ impl ClobClient {
         params: Option<&OpenOrderParams>,
         next_cursor: Option<&str>,
     ) -> ClientResult<Vec<OpenOrder>> {
-        let (signer, creds) = self.get_l2_parameters();
-        let method = Method::GET;
-        let endpoint = "/data/orders";
-        let (headers, _) =
-            create_l2_headers::<Value>(signer, creds, method.as_str(), endpoint, None)?;
+        Ok(self.get_orders_with_stats(params, next_cursor).await?.0)
+    }
 
+    /// [`ClobClient::get_orders`] plus how many pages were walked and the last
+    /// cursor the server reported.
+    pub async fn get_orders_with_stats(
+        &self,
+        params: Option<&OpenOrderParams>,
+        next_cursor: Option<&str>,
+    ) -> ClientResult<(Vec<OpenOrder>, PaginationStats)> {
         let query_params = match params {
             None => Vec::new(),
             Some(p) => p.to_query_params(),
         };
 
-        let mut next_cursor = next_cursor.unwrap_or(INITIAL_CURSOR).to_string();
         let mut output = Vec::new();
-        while next_cursor != END_CURSOR {
-            let req = self
-                .http_client
-                .request(method.clone(), format!("{}{endpoint}", &self.host))
-                .query(&query_params)
-                .query(&["next_cursor", &next_cursor]);
-
-            let r = headers
-                .clone()
-                .into_iter()
-                .fold(req, |r, (k, v)| r.header(HeaderName::from_static(k), v));
-
-            let resp = r.send().await?.json::<Value>().await?;
-            let new_cursor = resp["next_cursor"]
-                .as_str()
-                .expect("Failed to parse next cursor")
-                .to_owned();
-
-            next_cursor = new_cursor;
-
-            let results = resp["data"].clone();
-            let o = serde_json::from_value::<Vec<OpenOrder>>(results)
-                .expect("Failed to parse data from order response");
-            output.extend(o);
-        }
-        Ok(output)
+        let stats = self
+            .for_each_l2_page("/data/orders", &query_params, next_cursor, |orders| {
+                output.extend(orders);
+                Ok(())
+            })
+            .await?;
+        Ok((output, stats))
     }
 
     pub async fn get_order(&self, order_id: &str) -> ClientResult<OpenOrder> {
-        let (signer, creds) = self.get_l2_parameters();
+        let (signer, creds) = self.get_l2_parameters()?;
         let method = Method::GET;
         let endpoint = &format!("/data/order/{order_id}");
 
@@ -721,18 +1590,64 @@ impl ClobClient {
 
         let req = self.create_request_with_headers(method, endpoint, headers.into_iter());
 
-        Ok(req.send().await?.json::<OpenOrder>().await?)
+        self.send_json::<OpenOrder>(req).await
+    }
+
+    /// Trades that filled `order_id`, as taker or maker.
+    pub async fn get_order_trades(&self, order_id: &str) -> ClientResult<Vec<Trade>> {
+        let order = self.get_order(order_id).await?;
+        self.trades_for_order(&order).await
+    }
+
+    /// Filled size and average fill price of `order_id`.
+    pub async fn reconcile_order(&self, order_id: &str) -> ClientResult<FillReport> {
+        let order = self.get_order(order_id).await?;
+        let trades = self.trades_for_order(&order).await?;
+        Ok(FillReport::new(&order, &trades))
+    }
+
+    /// Trades that filled `order`, looked up by its associated trade ids. Only
+    /// when the server lists none for a matched order are all trades in its
+    /// asset scanned instead.
+    async fn trades_for_order(&self, order: &OpenOrder) -> ClientResult<Vec<Trade>> {
+        let mut params = TradeParams {
+            id: None,
+            maker_address: None,
+            market: None,
+            asset_id: None,
+            before: None,
+            after: None,
+        };
+        let mut trades = Vec::new();
+        if !order.associate_trades.is_empty() {
+            for trade_id in &order.associate_trades {
+                params.id = Some(trade_id.clone());
+                trades.extend(self.get_trades(Some(&params), None).await?);
+            }
+        } else if !order.size_matched.is_zero() {
+            params.asset_id = Some(order.asset_id.clone());
+            trades = self.get_trades(Some(&params), None).await?;
+        }
+        trades.retain(|t| t.fill_for_order(&order.id).is_some());
+        Ok(trades)
+    }
+
+    pub async fn get_order_by_hash(&self, order_hash: &str) -> ClientResult<OpenOrder> {
+        let order_hash = normalize_order_hash(order_hash)?;
+        let order = self.get_order(&order_hash).await?;
+        if !order.id.eq_ignore_ascii_case(&order_hash) {
+            return Err(anyhow!("Order {} returned for hash {order_hash}", order.id));
+        }
+        Ok(order)
     }
 
     pub async fn get_last_trade_price(&self, token_id: &str) -> ClientResult<Value> {
-        Ok(self
+        let req = self
             .http_client
-            .get(format!("{}/last-trade-price", &self.host))
-            .query(&[("token_id", token_id)])
-            .send()
-            .await?
-            .json::<Value>()
-            .await?)
+            .get(self.endpoint_url("/last-trade-price"))
+            .query(&[("token_id", token_id)]);
+
+        self.send_json::<Value>(req).await
     }
 
     pub async fn get_last_trade_prices(&self, token_ids: &[String]) -> ClientResult<Value> {
@@ -741,63 +1656,164 @@ impl ClobClient {
             .map(|b| HashMap::from([("token_id", b.clone())]))
             .collect::<Vec<HashMap<&str, String>>>();
 
-        Ok(self
+        let req = self
             .http_client
-            .post(format!("{}/last-trades-prices", &self.host))
-            .json(&v)
-            .send()
-            .await?
-            .json::<Value>()
-            .await?)
+            .post(self.endpoint_url("/last-trades-prices"))
+            .json(&v);
+
+        self.send_json::<Value>(req).await
     }
 
     pub async fn get_trades(
         &self,
         trade_params: Option<&TradeParams>,
         next_cursor: Option<&str>,
-    ) -> ClientResult<Vec<Value>> {
-        let (signer, creds) = self.get_l2_parameters();
-        let method = Method::GET;
-        let endpoint = "/data/trades";
-        let (headers, _) =
-            create_l2_headers::<Value>(signer, creds, method.as_str(), endpoint, None)?;
+    ) -> ClientResult<Vec<Trade>> {
+        Ok(self
+            .get_trades_with_stats(trade_params, next_cursor)
+            .await?
+            .0)
+    }
+
+    /// [`ClobClient::get_trades`] plus how many pages were walked and the last
+    /// cursor the server reported.
+    pub async fn get_trades_with_stats(
+        &self,
+        trade_params: Option<&TradeParams>,
+        next_cursor: Option<&str>,
+    ) -> ClientResult<(Vec<Trade>, PaginationStats)> {
+        let mut output = Vec::new();
+        let stats = self
+            .for_each_trades_page(trade_params, next_cursor, |trades| {
+                output.extend(trades);
+                Ok(())
+            })
+            .await?;
+        Ok((output, stats))
+    }
+
+    /// Writes every trade matching `trade_params` to `writer` as newline delimited
+    /// JSON, one page at a time, and returns how many were written.
+    pub async fn export_trades<W: Write>(
+        &self,
+        trade_params: Option<&TradeParams>,
+        writer: &mut W,
+    ) -> ClientResult<usize> {
+        let mut count = 0;
+        self.for_each_trades_page(trade_params, None, |trades| {
+            for trade in &trades {
+                serde_json::to_writer(&mut *writer, trade)?;
+                writer.write_all(b"\n")?;
+            }
+            count += trades.len();
+            Ok(())
+        })
+        .await?;
+        writer.flush()?;
+        Ok(count)
+    }
 
+    async fn for_each_trades_page(
+        &self,
+        trade_params: Option<&TradeParams>,
+        next_cursor: Option<&str>,
+        on_page: impl FnMut(Vec<Trade>) -> ClientResult<()>,
+    ) -> ClientResult<PaginationStats> {
         let query_params = match trade_params {
             None => Vec::new(),
             Some(p) => p.to_query_params(),
         };
+        self.for_each_l2_page("/data/trades", &query_params, next_cursor, on_page)
+            .await
+    }
 
-        let mut next_cursor = next_cursor.unwrap_or(INITIAL_CURSOR).to_string();
+    /// Walks an L2 authenticated, cursor paginated endpoint, handing each page's
+    /// `data` to `on_page` as soon as it arrives.
+    async fn for_each_l2_page<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        query_params: &(impl Serialize + ?Sized),
+        next_cursor: Option<&str>,
+        mut on_page: impl FnMut(Vec<T>) -> ClientResult<()>,
+    ) -> ClientResult<PaginationStats> {
+        let (signer, creds) = self.get_l2_parameters()?;
+        let method = Method::GET;
+        let (headers, _) =
+            create_l2_headers::<Value>(signer, creds, method.as_str(), endpoint, None)?;
 
-        let mut output = Vec::new();
-        while next_cursor != END_CURSOR {
+        let first = next_cursor.unwrap_or(INITIAL_CURSOR).to_string();
+        let mut seen = HashSet::from([first.clone()]);
+        let mut next_cursor = Some(first);
+        let mut stats = PaginationStats::default();
+        while let Some(cursor) = next_cursor {
             let req = self
                 .http_client
-                .request(method.clone(), format!("{}{endpoint}", &self.host))
-                .query(&query_params)
-                .query(&["next_cursor", &next_cursor]);
+                .request(method.clone(), self.endpoint_url(endpoint))
+                .query(query_params)
+                .query(&[("next_cursor", &cursor)]);
 
             let r = headers
                 .clone()
                 .into_iter()
                 .fold(req, |r, (k, v)| r.header(HeaderName::from_static(k), v));
 
-            let resp = r.send().await?.json::<Value>().await?;
-            let new_cursor = resp["next_cursor"]
-                .as_str()
-                .expect("Failed to parse next cursor")
-                .to_owned();
-
-            next_cursor = new_cursor;
+            let resp = self.send_json::<Value>(r).await?;
+            stats.last_cursor = resp["next_cursor"].as_str().map(str::to_owned);
+            next_cursor = next_page_cursor(stats.last_cursor.as_deref(), &mut seen);
 
             let results = resp["data"].clone();
-            output.push(results);
+            let page = serde_json::from_value::<Vec<T>>(results)
+                .with_context(|| format!("Failed to parse data from {endpoint} response"))?;
+            stats.pages += 1;
+            stats.total_items += page.len();
+            on_page(page)?;
         }
-        Ok(output)
+        Ok(stats)
+    }
+
+    /// Address that orders are placed from: the funder if an order builder is
+    /// configured, otherwise the signer.
+    fn trading_address(&self) -> Option<Address> {
+        match &self.order_builder {
+            Some(ob) => Some(ob.funder()),
+            None => Some(self.signer.as_ref()?.address()),
+        }
+    }
+
+    async fn get_trades_with_role(
+        &self,
+        trade_params: Option<&TradeParams>,
+        role: TradeRole,
+    ) -> ClientResult<Vec<Trade>> {
+        let address = self
+            .trading_address()
+            .context("Signer is not set")?
+            .to_string();
+        let trades = self.get_trades(trade_params, None).await?;
+        Ok(trades
+            .into_iter()
+            .filter(|t| t.role(&address) == Some(role))
+            .collect())
+    }
+
+    pub async fn get_maker_trades(
+        &self,
+        trade_params: Option<&TradeParams>,
+    ) -> ClientResult<Vec<Trade>> {
+        self.get_trades_with_role(trade_params, TradeRole::Maker)
+            .await
+    }
+
+    pub async fn get_taker_trades(
+        &self,
+        trade_params: Option<&TradeParams>,
+    ) -> ClientResult<Vec<Trade>> {
+        self.get_trades_with_role(trade_params, TradeRole::Taker)
+            .await
     }
 
     pub async fn get_notifications(&self) -> ClientResult<Value> {
-        let (signer, creds) = self.get_l2_parameters();
+        let (signer, creds) = self.get_l2_parameters()?;
 
         let method = Method::GET;
         let endpoint = "/notifications";
@@ -806,23 +1822,20 @@ impl ClobClient {
 
         let req = self.create_request_with_headers(method, endpoint, headers.into_iter());
 
-        Ok(req
-            .query(&[(
-                "signature_type",
-                &self
-                    .order_builder
-                    .as_ref()
-                    .expect("Orderbuilder not set")
-                    .get_sig_type(),
-            )])
-            .send()
-            .await?
-            .json::<Value>()
-            .await?)
+        let req = req.query(&[(
+            "signature_type",
+            &self
+                .order_builder
+                .as_ref()
+                .expect("Orderbuilder not set")
+                .get_sig_type(),
+        )]);
+
+        self.send_json::<Value>(req).await
     }
 
     pub async fn drop_notifications(&self, ids: &[String]) -> ClientResult<Value> {
-        let (signer, creds) = self.get_l2_parameters();
+        let (signer, creds) = self.get_l2_parameters()?;
 
         let method = Method::DELETE;
         let endpoint = "/notifications";
@@ -831,12 +1844,9 @@ impl ClobClient {
 
         let req = self.create_request_with_headers(method, endpoint, headers.into_iter());
 
-        Ok(req
-            .query(&[("ids", ids.join(","))])
-            .send()
-            .await?
-            .json::<Value>()
-            .await?)
+        let req = req.query(&[("ids", ids.join(","))]);
+
+        self.send_json::<Value>(req).await
     }
 
     pub async fn get_balance_allowance(
@@ -855,7 +1865,7 @@ impl ClobClient {
 
         let query_params = params.to_query_params();
 
-        let (signer, creds) = self.get_l2_parameters();
+        let (signer, creds) = self.get_l2_parameters()?;
 
         let method = Method::GET;
         let endpoint = "/balance-allowance";
@@ -863,12 +1873,9 @@ impl ClobClient {
             create_l2_headers::<Value>(signer, creds, method.as_str(), endpoint, None)?;
 
         let req = self.create_request_with_headers(method, endpoint, headers.into_iter());
-        Ok(req
-            .query(&query_params)
-            .send()
-            .await?
-            .json::<Value>()
-            .await?)
+        let req = req.query(&query_params);
+
+        self.send_json::<Value>(req).await
     }
 
     pub async fn update_balance_allowance(
@@ -887,7 +1894,7 @@ impl ClobClient {
 
         let query_params = params.to_query_params();
 
-        let (signer, creds) = self.get_l2_parameters();
+        let (signer, creds) = self.get_l2_parameters()?;
 
         let method = Method::GET;
         let endpoint = "/balance-allowance/update";
@@ -895,16 +1902,13 @@ impl ClobClient {
             create_l2_headers::<Value>(signer, creds, method.as_str(), endpoint, None)?;
 
         let req = self.create_request_with_headers(method, endpoint, headers.into_iter());
-        Ok(req
-            .query(&query_params)
-            .send()
-            .await?
-            .json::<Value>()
-            .await?)
+        let req = req.query(&query_params);
+
+        self.send_json::<Value>(req).await
     }
 
     pub async fn is_order_scoring(&self, order_id: &str) -> ClientResult<bool> {
-        let (signer, creds) = self.get_l2_parameters();
+        let (signer, creds) = self.get_l2_parameters()?;
 
         let method = Method::GET;
         let endpoint = "/order-scoring";
@@ -912,12 +1916,9 @@ impl ClobClient {
             create_l2_headers::<Value>(signer, creds, method.as_str(), endpoint, None)?;
         let req = self.create_request_with_headers(method, endpoint, headers.into_iter());
 
-        Ok(req
-            .query(&[("order_id", order_id)])
-            .send()
-            .await?
-            .json::<Value>()
-            .await?["scoring"]
+        let req = req.query(&[("order_id", order_id)]);
+
+        Ok(self.send_json::<Value>(req).await?["scoring"]
             .as_bool()
             .expect("Unknown scoring value"))
     }
@@ -926,7 +1927,7 @@ impl ClobClient {
         &self,
         order_ids: &[&str],
     ) -> ClientResult<HashMap<String, bool>> {
-        let (signer, creds) = self.get_l2_parameters();
+        let (signer, creds) = self.get_l2_parameters()?;
 
         let method = Method::POST;
         let endpoint = "/orders-scoring";
@@ -936,13 +1937,77 @@ impl ClobClient {
         let req = self.create_request_with_headers(method, endpoint, headers.into_iter());
         let body_str = body_str.expect("body string missing for orders_scoring");
 
-        Ok(req
+        let req = req
             .header(reqwest::header::CONTENT_TYPE, "application/json")
-            .body(body_str)
-            .send()
-            .await?
-            .json::<HashMap<String, bool>>()
-            .await?)
+            .body(body_str);
+
+        self.send_json::<HashMap<String, bool>>(req).await
+    }
+
+    /// All open orders, keyed by market condition id.
+    pub async fn get_open_orders_by_market(&self) -> ClientResult<HashMap<String, Vec<OpenOrder>>> {
+        Ok(group_orders_by_market(self.get_orders(None, None).await?))
+    }
+
+    pub async fn reward_summary(&self) -> ClientResult<Vec<RewardStatus>> {
+        let orders = self.get_orders(None, None).await?;
+        if orders.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let order_ids = orders.iter().map(|o| o.id.as_str()).collect::<Vec<_>>();
+        let scoring = self.are_orders_scoring(&order_ids).await?;
+
+        let mut rewards = HashMap::new();
+        for o in &orders {
+            if !rewards.contains_key(&o.market) {
+                let market = self.get_market(&o.market).await?;
+                rewards.insert(o.market.clone(), market.rewards);
+            }
+        }
+
+        Ok(collect_reward_statuses(&orders, &scoring, &rewards))
+    }
+
+    /// Reward payouts of the authenticated user, following the cursor to the end.
+    /// Served by the CLOB host rather than the data API, since it needs L2 auth.
+    pub async fn get_rewards_history(
+        &self,
+        params: Option<&RewardsHistoryParams>,
+        next_cursor: Option<&str>,
+    ) -> ClientResult<Vec<RewardEarning>> {
+        let query_params = match params {
+            None => Vec::new(),
+            Some(p) => p.to_query_params(),
+        };
+
+        let mut output = Vec::new();
+        self.for_each_l2_page("/rewards/user", &query_params, next_cursor, |earnings| {
+            output.extend(earnings);
+            Ok(())
+        })
+        .await?;
+        Ok(output)
+    }
+
+    pub async fn get_positions(
+        &self,
+        params: Option<&PositionParams>,
+    ) -> ClientResult<Vec<Position>> {
+        let user = self
+            .trading_address()
+            .context("Signer is not set")?
+            .to_string();
+        let query_params = match params {
+            None => Vec::new(),
+            Some(p) => p.to_query_params(),
+        };
+
+        let req = self.create_data_api_l2_request(Method::GET, "/positions")?;
+
+        let req = req.query(&[("user", user)]).query(&query_params);
+
+        self.send_json::<Vec<Position>>(req).await
     }
 
     pub async fn get_sampling_markets(
@@ -951,14 +2016,12 @@ impl ClobClient {
     ) -> ClientResult<MarketsResponse> {
         let next_cursor = next_cursor.unwrap_or(INITIAL_CURSOR);
 
-        Ok(self
+        let req = self
             .http_client
-            .get(format!("{}/sampling-markets", &self.host))
-            .query(&[("next_cursor", next_cursor)])
-            .send()
-            .await?
-            .json::<MarketsResponse>()
-            .await?)
+            .get(self.endpoint_url("/sampling-markets"))
+            .query(&[("next_cursor", next_cursor)]);
+
+        self.send_json::<MarketsResponse>(req).await
     }
 
     pub async fn get_sampling_simplified_markets(
@@ -967,27 +2030,46 @@ impl ClobClient {
     ) -> ClientResult<SimplifiedMarketsResponse> {
         let next_cursor = next_cursor.unwrap_or(INITIAL_CURSOR);
 
-        Ok(self
+        let req = self
             .http_client
-            .get(format!("{}/sampling-simplified-markets", &self.host))
-            .query(&[("next_cursor", next_cursor)])
-            .send()
-            .await?
-            .json::<SimplifiedMarketsResponse>()
-            .await?)
+            .get(self.endpoint_url("/sampling-simplified-markets"))
+            .query(&[("next_cursor", next_cursor)]);
+
+        self.send_json::<SimplifiedMarketsResponse>(req).await
     }
 
     pub async fn get_markets(&self, next_cursor: Option<&str>) -> ClientResult<MarketsResponse> {
         let next_cursor = next_cursor.unwrap_or(INITIAL_CURSOR);
 
-        Ok(self
+        let req = self
             .http_client
-            .get(format!("{}/markets", &self.host))
-            .query(&[("next_cursor", next_cursor)])
-            .send()
-            .await?
-            .json::<MarketsResponse>()
-            .await?)
+            .get(self.endpoint_url("/markets"))
+            .query(&[("next_cursor", next_cursor)]);
+
+        self.send_json::<MarketsResponse>(req).await
+    }
+
+    /// Yields every market, fetching one page at a time. The stream ends after
+    /// the first error.
+    pub fn stream_markets(&self) -> impl Stream<Item = ClientResult<Market>> + '_ {
+        self.stream_cursor_pages(move |cursor| async move {
+            let page = self.get_markets(Some(&cursor)).await?;
+            Ok((page.data, page.next_cursor))
+        })
+    }
+
+    /// Pages through all markets, keeping those whose game starts within
+    /// `[from, to)`.
+    #[cfg(feature = "chrono")]
+    pub async fn get_markets_starting_between(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> ClientResult<Vec<Market>> {
+        self.stream_markets()
+            .try_filter(|m| future::ready(m.starts_between(from, to)))
+            .try_collect()
+            .await
     }
 
     pub async fn get_simplified_markets(
@@ -996,36 +2078,178 @@ impl ClobClient {
     ) -> ClientResult<SimplifiedMarketsResponse> {
         let next_cursor = next_cursor.unwrap_or(INITIAL_CURSOR);
 
-        Ok(self
+        let req = self
             .http_client
-            .get(format!("{}/simplified-markets", &self.host))
-            .query(&[("next_cursor", next_cursor)])
-            .send()
-            .await?
-            .json::<SimplifiedMarketsResponse>()
-            .await?)
+            .get(self.endpoint_url("/simplified-markets"))
+            .query(&[("next_cursor", next_cursor)]);
+
+        self.send_json::<SimplifiedMarketsResponse>(req).await
+    }
+
+    /// Yields every simplified market, fetching one page at a time. The stream
+    /// ends after the first error.
+    pub fn stream_simplified_markets(
+        &self,
+    ) -> impl Stream<Item = ClientResult<SimplifiedMarket>> + '_ {
+        self.stream_cursor_pages(move |cursor| async move {
+            let page = self.get_simplified_markets(Some(&cursor)).await?;
+            Ok((page.data, page.next_cursor))
+        })
+    }
+
+    /// Flattens the cursor paginated endpoint behind `fetch_page`, which maps a
+    /// cursor to that page's items and `next_cursor`.
+    fn stream_cursor_pages<'a, T, F, Fut>(
+        &'a self,
+        fetch_page: F,
+    ) -> impl Stream<Item = ClientResult<T>> + 'a
+    where
+        T: 'a,
+        F: Fn(String) -> Fut + 'a,
+        Fut: Future<Output = ClientResult<(Vec<T>, Option<String>)>> + 'a,
+    {
+        let state = (
+            VecDeque::new(),
+            Some(INITIAL_CURSOR.to_owned()),
+            HashSet::from([INITIAL_CURSOR.to_owned()]),
+            fetch_page,
+        );
+        stream::unfold(
+            state,
+            |(mut pending, mut cursor, mut seen, fetch_page)| async move {
+                loop {
+                    if let Some(item) = pending.pop_front() {
+                        return Some((Ok(item), (pending, cursor, seen, fetch_page)));
+                    }
+                    let (data, next_cursor) = match fetch_page(cursor?).await {
+                        Ok(page) => page,
+                        Err(e) => return Some((Err(e), (pending, None, seen, fetch_page))),
+                    };
+                    cursor = next_page_cursor(next_cursor.as_deref(), &mut seen);
+                    pending.extend(data);
+                }
+            },
+        )
+    }
+
+    /// `stream_simplified_markets` limited to markets that pay liquidity rewards.
+    pub fn stream_reward_markets(&self) -> impl Stream<Item = ClientResult<SimplifiedMarket>> + '_ {
+        self.stream_simplified_markets()
+            .filter(|m| future::ready(m.as_ref().map_or(true, |m| m.rewards.is_eligible())))
+    }
+
+    /// Midpoints of both tokens of a binary market, fetched in one batch.
+    pub async fn get_market_prices(&self, condition_id: &str) -> ClientResult<BinaryMarketPrices> {
+        let market = self.get_market(condition_id).await?;
+        let (yes, no) = market.yes_no_tokens();
+        let ids = [yes.token_id.clone(), no.token_id.clone()];
+        let mut midpoints = self.get_midpoints_ordered(&ids).await?.into_iter();
+        let mut next = || midpoints.next().and_then(|(_, mid)| mid);
+        Ok(BinaryMarketPrices::new(next(), next()))
     }
 
     pub async fn get_market(&self, condition_id: &str) -> ClientResult<Market> {
-        Ok(self
+        if let Some(market) = self.markets.as_ref().and_then(|m| m.get(condition_id)) {
+            return Ok(market);
+        }
+
+        let req = self
             .http_client
-            .get(format!("{}/markets/{condition_id}", &self.host))
-            .send()
-            .await?
-            .json::<Market>()
-            .await?)
+            .get(self.endpoint_url(&format!("/markets/{condition_id}")));
+
+        let market = self.send_json::<Market>(req).await?;
+        if let Some(markets) = &self.markets {
+            markets.insert(condition_id, market.clone());
+        }
+        Ok(market)
+    }
+
+    /// Resolves the slug from a market's polymarket.com URL. The CLOB has no slug
+    /// filter, so this pages through markets until one matches.
+    pub async fn get_market_by_slug(&self, slug: &str) -> ClientResult<Option<Market>> {
+        let matching = self
+            .stream_markets()
+            .try_filter(|m| future::ready(m.market_slug == slug));
+        let market = std::pin::pin!(matching).try_next().await?;
+        if let (Some(market), Some(markets)) = (&market, &self.markets) {
+            markets.insert(&market.condition_id, market.clone());
+        }
+        Ok(market)
+    }
+
+    /// Looks up the market holding `token_id` and returns its other token id.
+    pub async fn complementary_token(&self, token_id: &str) -> ClientResult<Option<String>> {
+        let condition_id = self.get_order_book(token_id).await?.market;
+        let market = self.get_market(&condition_id).await?;
+        Ok(market.complement(token_id).map(str::to_owned))
+    }
+
+    #[cfg(feature = "chrono")]
+    async fn check_accepting_order_time(&self, token_id: &str) -> ClientResult<()> {
+        let condition_id = self.get_order_book(token_id).await?.market;
+        let market = self.get_market(&condition_id).await?;
+        match market.accepting_orders_from() {
+            Some(from) if Utc::now() < from => Err(anyhow!(
+                "Market {condition_id} is not accepting orders until {}",
+                from.to_rfc3339()
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    #[cfg(not(feature = "chrono"))]
+    async fn check_accepting_order_time(&self, _token_id: &str) -> ClientResult<()> {
+        Err(anyhow!(
+            "check_accepting_order_time needs the chrono feature"
+        ))
+    }
+
+    pub async fn can_trade(&self, condition_id: &str) -> ClientResult<MarketStatus> {
+        Ok(self.get_market(condition_id).await?.status())
     }
 
     pub async fn get_market_trades_events(&self, condition_id: &str) -> ClientResult<Value> {
-        Ok(self
+        let req = self
             .http_client
-            .get(format!(
-                "{}/live-activity/events/{condition_id}",
-                &self.host
-            ))
-            .send()
-            .await?
-            .json::<Value>()
-            .await?)
+            .get(self.endpoint_url(&format!("/live-activity/events/{condition_id}")));
+
+        self.send_json::<Value>(req).await
+    }
+
+    /// Polls the market activity feed every `interval` and yields events not seen
+    /// in the previous poll. Errors are yielded without ending the stream.
+    pub fn stream_market_trades<'a>(
+        &'a self,
+        condition_id: &'a str,
+        interval: Duration,
+    ) -> impl Stream<Item = ClientResult<MarketTradeEvent>> + 'a {
+        let state = (HashSet::new(), VecDeque::new(), true);
+        stream::unfold(
+            state,
+            move |(mut seen, mut pending, mut first)| async move {
+                loop {
+                    if let Some(event) = pending.pop_front() {
+                        return Some((Ok(event), (seen, pending, first)));
+                    }
+                    if !first {
+                        tokio::time::sleep(interval).await;
+                    }
+                    first = false;
+
+                    let events = self
+                        .get_market_trades_events(condition_id)
+                        .await
+                        .and_then(|v| Ok(serde_json::from_value::<Vec<MarketTradeEvent>>(v)?));
+                    match events {
+                        Ok(events) => {
+                            let (fresh, keys) = new_trade_events(&seen, events);
+                            seen = keys;
+                            pending.extend(fresh);
+                        }
+                        Err(e) => return Some((Err(e), (seen, pending, first))),
+                    }
+                }
+            },
+        )
     }
 }
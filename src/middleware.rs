@@ -0,0 +1,44 @@
+use reqwest::{Request, Response};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Hooks run around every request [`crate::ClobClient`] sends, in the order
+/// they were added. Both hooks may modify what they are given.
+pub trait Middleware: Send + Sync {
+    /// Called with the fully built request, including auth headers.
+    fn on_request(&self, _request: &mut Request) {}
+
+    /// Called with the response before its status is checked.
+    fn on_response(&self, _response: &mut Response) {}
+}
+
+/// Counts requests per endpoint path. Clones share the same counts, so keep a
+/// clone around to read them after handing one to the client.
+#[derive(Clone, Debug, Default)]
+pub struct RequestCounter {
+    counts: Arc<Mutex<HashMap<String, u64>>>,
+}
+
+impl RequestCounter {
+    /// Number of requests sent to `path`, e.g. `/book`.
+    pub fn count(&self, path: &str) -> u64 {
+        self.counts
+            .lock()
+            .unwrap()
+            .get(path)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Snapshot of all counts keyed by path.
+    pub fn counts(&self) -> HashMap<String, u64> {
+        self.counts.lock().unwrap().clone()
+    }
+}
+
+impl Middleware for RequestCounter {
+    fn on_request(&self, request: &mut Request) {
+        let path = request.url().path().to_owned();
+        *self.counts.lock().unwrap().entry(path).or_default() += 1;
+    }
+}
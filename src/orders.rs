@@ -1,5 +1,5 @@
-use alloy_primitives::Address;
 use alloy_primitives::U256;
+use alloy_primitives::{Address, PrimitiveSignature, B256};
 use anyhow::anyhow;
 use anyhow::{Context, Result};
 use rand::thread_rng;
@@ -7,14 +7,15 @@ use rand::Rng;
 use rust_decimal::Decimal;
 use rust_decimal::RoundingStrategy::{AwayFromZero, MidpointTowardZero, ToZero};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::config::get_contract_config;
-use crate::eth_utils::sign_order_message;
-use crate::eth_utils::Order;
-use crate::utils::get_current_unix_time_secs;
+use crate::config::{check_chain_supported, get_contract_config, ContractConfig};
+use crate::errors::{InsufficientLiquidity, PriceBound, PriceOutOfRange};
+use crate::eth_utils::{order_digest, sign_order_message, Order, RecoveryIdFormat, SignatureParts};
+use crate::utils::{get_current_unix_time_secs, parse_token_id, token_id_to_decimal_string};
 use crate::{
-    CreateOrderOptions, EthSigner, ExtraOrderArgs, MarketOrderArgs, OrderArgs, OrderSummary, Side,
+    ClientResult, CreateOrderOptions, EthSigner, ExtraOrderArgs, MarketOrderArgs, OrderArgs,
+    OrderBookSummary, OrderSummary, PriceRounding, Side,
 };
 
 use std::collections::HashMap;
@@ -30,20 +31,56 @@ pub enum SigType {
     EmailOrMagic = 1,
     /// Browser wallet proxy / Polymarket proxy wallet (py/TS signature_type = 2)
     BrowserWalletProxy = 2,
-    /// Gnosis safe backed Polymarket wallets
+    /// Gnosis safe backed Polymarket wallets. The safe is the order maker (set it as
+    /// the funder) while its owner EOA signs orders and auth messages with plain
+    /// ECDSA. EIP-1271 contract signatures are not supported yet.
     GnosisSafe = 3,
 }
 
+impl SigType {
+    /// Value put in the order's `signatureType` field.
+    pub fn as_u8(self) -> u8 {
+        self as u8
+    }
+
+    fn requires_funder(self) -> bool {
+        !matches!(self, SigType::Eoa)
+    }
+}
+
 pub struct OrderBuilder {
     signer: Box<dyn EthSigner>,
     sig_type: SigType,
     funder: Address,
+    round_configs: HashMap<Decimal, RoundConfig>,
 }
 
+/// Number of decimal places used when rounding an order for a given tick size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct RoundConfig {
-    price: u32,
-    size: u32,
-    amount: u32,
+    pub price: u32,
+    pub size: u32,
+    pub amount: u32,
+}
+
+impl RoundConfig {
+    pub fn new(price: u32, size: u32, amount: u32) -> Self {
+        RoundConfig {
+            price,
+            size,
+            amount,
+        }
+    }
+}
+
+/// Result of walking the book for a market order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MarketPriceFill {
+    /// Price of the last level needed (or available, when the book can't fill the amount).
+    pub price: Option<Decimal>,
+    /// Amount that can be matched, at most the requested amount.
+    pub filled_amount: Decimal,
+    pub fully_matched: bool,
 }
 
 fn generate_seed() -> u64 {
@@ -53,7 +90,12 @@ fn generate_seed() -> u64 {
     a as u64
 }
 
-#[derive(Debug, Serialize)]
+/// A signed order as posted to the exchange.
+///
+/// It cannot be rebuilt from an [`crate::OpenOrder`]: order queries omit the
+/// salt, signer, amounts, nonce, fee rate and signature. Keep the serialized
+/// request itself (e.g. as JSON) to re-post or re-hash it after a restart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SignedOrderRequest {
     pub salt: u64,
@@ -108,15 +150,320 @@ static ROUNDING_CONFIG: LazyLock<HashMap<Decimal, RoundConfig>> = LazyLock::new(
     ])
 });
 
+/// Decimals of USDC and of the conditional tokens; on-chain amounts are scaled by 10^6.
+pub const USDC_DECIMALS: u32 = 6;
+/// Decimal places the exchange accepts for USDC (quote) amounts.
+pub const QUOTE_PRECISION: u32 = 2;
+/// Decimal places the exchange accepts for outcome token (base) amounts.
+pub const BASE_PRECISION: u32 = 4;
+
 fn decimal_to_token_u32(amt: Decimal) -> u32 {
-    let mut amt = Decimal::from_scientific("1e6").expect("1e6 is not scientific") * amt;
+    let mut amt = Decimal::from(10u32.pow(USDC_DECIMALS)) * amt;
     if amt.scale() > 0 {
         amt = amt.round_dp_with_strategy(0, MidpointTowardZero);
     }
     amt.try_into().expect("Couldn't round decimal to integer")
 }
 
+/// Signs a limit order without any network access.
+///
+/// Unlike [`crate::ClobClient::create_order`], nothing is fetched from the server,
+/// so `options` must carry both `tick_size` and `neg_risk`. The returned
+/// request can be serialized and handed to an online machine for posting.
+pub fn sign_order(
+    signer: Box<dyn EthSigner>,
+    chain_id: u64,
+    order_args: &OrderArgs,
+    options: CreateOrderOptions,
+    extras: Option<ExtraOrderArgs>,
+) -> ClientResult<SignedOrderRequest> {
+    check_chain_supported(chain_id)?;
+    let tick_size = options
+        .tick_size
+        .context("Cannot sign order without tick size")?;
+    if options.neg_risk.is_none() {
+        return Err(anyhow!("Cannot sign order without neg_risk"));
+    }
+    if !options.skip_price_validation {
+        check_price_in_range(order_args.price, tick_size)?;
+    }
+
+    OrderBuilder::new(signer, None, None).create_order(
+        chain_id,
+        order_args,
+        0,
+        &extras.unwrap_or_default(),
+        options,
+    )
+}
+
+/// Copy of `positions` ordered best price first for a taker on `side`: asks
+/// ascending for `BUY`, bids descending for `SELL`. `/book` lists levels
+/// worst-first, so callers can't rely on the order they arrive in.
+fn best_first(positions: &[OrderSummary], side: Side) -> Vec<OrderSummary> {
+    let mut levels = positions.to_vec();
+    match side {
+        Side::BUY => levels.sort_by_key(|l| l.price),
+        Side::SELL => levels.sort_by_key(|l| std::cmp::Reverse(l.price)),
+    }
+    levels
+}
+
+/// Walks `positions` best price first for a taker on `side`, accumulating
+/// `measure` of each level until `amount_to_match` is covered. `visit` sees
+/// each level touched with the part of its `measure` taken.
+fn walk_book(
+    positions: &[OrderSummary],
+    side: Side,
+    amount_to_match: Decimal,
+    measure: impl Fn(&OrderSummary) -> Decimal,
+    mut visit: impl FnMut(&OrderSummary, Decimal),
+) -> MarketPriceFill {
+    let mut sum = Decimal::ZERO;
+    let mut price = None;
+
+    for p in best_first(positions, side) {
+        let level = measure(&p);
+        price = Some(p.price);
+        if sum + level >= amount_to_match {
+            visit(&p, amount_to_match - sum);
+            return MarketPriceFill {
+                price,
+                filled_amount: amount_to_match,
+                fully_matched: true,
+            };
+        }
+        visit(&p, level);
+        sum += level;
+    }
+    MarketPriceFill {
+        price,
+        filled_amount: sum,
+        fully_matched: false,
+    }
+}
+
+/// Worst price reached when taking `size` shares from `positions` on `side`
+/// (asks for `BUY`, bids for `SELL`), or `None` if the levels don't hold that
+/// many shares.
+pub fn executable_price(positions: &[OrderSummary], side: Side, size: Decimal) -> Option<Decimal> {
+    let fill = walk_book(positions, side, size, |p| p.size, |_, _| {});
+    fill.fully_matched.then_some(fill.price).flatten()
+}
+
+/// Average price per share when spending (or, for `SELL`, receiving)
+/// `notional` USDC across `positions` best price first, or `None` if the levels
+/// don't hold that much.
+pub fn vwap(positions: &[OrderSummary], side: Side, notional: Decimal) -> Option<Decimal> {
+    if notional <= Decimal::ZERO {
+        return None;
+    }
+    let mut shares = Decimal::ZERO;
+    let fill = walk_book(
+        positions,
+        side,
+        notional,
+        |p| p.size * p.price,
+        |p, taken| {
+            if p.price > Decimal::ZERO {
+                shares += taken / p.price;
+            }
+        },
+    );
+    fill.fully_matched.then(|| notional / shares)
+}
+
+/// Moves `price` onto the `tick_size` grid without making it more aggressive:
+/// bids are rounded down and asks up, then kept within the valid price range.
+/// Fails if `tick_size` is not positive.
+pub fn snap_to_tick(price: Decimal, tick_size: Decimal, side: Side) -> Result<Decimal> {
+    check_tick_size(tick_size)?;
+    Ok(round_to_tick(price, tick_size, side)
+        .clamp(tick_size, Decimal::ONE - tick_size)
+        .normalize())
+}
+
+/// Price one tick better than `best` on `side`: above the best bid for `BUY`,
+/// below the best ask for `SELL`. `None` if that leaves the valid price range or
+/// would reach `opposite`, the best price on the other side.
+pub(crate) fn improved_price(
+    best: Decimal,
+    opposite: Option<Decimal>,
+    tick_size: Decimal,
+    side: Side,
+) -> Option<Decimal> {
+    let price = match side {
+        Side::BUY => round_to_tick(best, tick_size, side) + tick_size,
+        Side::SELL => round_to_tick(best, tick_size, side) - tick_size,
+    };
+    let crosses = opposite.is_some_and(|o| match side {
+        Side::BUY => price >= o,
+        Side::SELL => price <= o,
+    });
+    (check_price_in_range(price, tick_size).is_ok() && !crosses).then(|| price.normalize())
+}
+
+/// Rejects tick sizes [`round_to_tick`] can't divide by.
+pub(crate) fn check_tick_size(tick_size: Decimal) -> Result<()> {
+    if tick_size <= Decimal::ZERO {
+        return Err(anyhow!("Tick size must be positive, got {tick_size}"));
+    }
+    Ok(())
+}
+
+pub(crate) fn round_to_tick(price: Decimal, tick_size: Decimal, side: Side) -> Decimal {
+    let ticks = price / tick_size;
+    let ticks = match side {
+        Side::BUY => ticks.floor(),
+        Side::SELL => ticks.ceil(),
+    };
+    (ticks * tick_size).normalize()
+}
+
+/// Checks that `price` lies within `[tick_size, 1 - tick_size]`, reporting the
+/// violated bound otherwise.
+pub fn check_price_in_range(price: Decimal, tick_size: Decimal) -> Result<(), PriceOutOfRange> {
+    let min_price = tick_size;
+    let max_price = Decimal::ONE - tick_size;
+
+    let violated = if price < min_price {
+        PriceBound::Min
+    } else if price > max_price {
+        PriceBound::Max
+    } else {
+        return Ok(());
+    };
+
+    Err(PriceOutOfRange {
+        price,
+        min_price,
+        max_price,
+        violated,
+    })
+}
+
+/// Applies the `max_book_levels` / `max_slippage_bps` guards of `options` to a
+/// market buy that fills at `price` by walking the `positions` asks best first.
+pub(crate) fn check_market_order_liquidity(
+    positions: &[OrderSummary],
+    amount_to_match: Decimal,
+    price: Decimal,
+    midpoint: Option<Decimal>,
+    options: &CreateOrderOptions,
+) -> Result<()> {
+    if let Some(max_levels) = options.max_book_levels {
+        let mut levels = 0;
+        walk_book(
+            positions,
+            Side::BUY,
+            amount_to_match,
+            |p| p.size * p.price,
+            |_, _| levels += 1,
+        );
+        if levels > max_levels {
+            return Err(anyhow!(
+                "Filling {amount_to_match} would cross {levels} book levels, more than the allowed {max_levels}"
+            ));
+        }
+    }
+
+    if let Some(max_slippage_bps) = options.max_slippage_bps {
+        let midpoint = midpoint.context("Cannot check slippage without a book midpoint")?;
+        let slippage_bps = (price - midpoint).abs() / midpoint * Decimal::from(10_000);
+        if slippage_bps > Decimal::from(max_slippage_bps) {
+            return Err(anyhow!(
+                "Fill price {price} is {} bps from midpoint {midpoint}, more than the allowed {max_slippage_bps} bps",
+                slippage_bps.round_dp(2).normalize()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Fat finger guard: fails if a limit order's price is more than `max_bps`
+/// through the best opposite quote of `book`, e.g. a bid far above the best
+/// ask. Passes when the opposite side is empty.
+pub(crate) fn check_price_through_book(
+    order_args: &OrderArgs,
+    book: &OrderBookSummary,
+    max_bps: u32,
+) -> Result<()> {
+    let (best, through) = match order_args.side {
+        Side::BUY => match book.best_ask() {
+            Some(ask) => (ask.price, order_args.price - ask.price),
+            None => return Ok(()),
+        },
+        Side::SELL => match book.best_bid() {
+            Some(bid) => (bid.price, bid.price - order_args.price),
+            None => return Ok(()),
+        },
+    };
+    let through_bps = through / best * Decimal::from(10_000);
+    if through_bps > Decimal::from(max_bps) {
+        return Err(anyhow!(
+            "{} price {} is {} bps through the best opposite quote {best}, more than the allowed {max_bps} bps",
+            order_args.side.as_str(),
+            order_args.price,
+            through_bps.round_dp(2).normalize()
+        ));
+    }
+    Ok(())
+}
+
+/// Fails if truncating `size` to `decimals` places loses all of it, leaves it
+/// below the market's `min_size`, or loses more than the `max_loss` fraction
+/// when given. `decimals` comes from the `RoundConfig`: the CLOB takes share
+/// sizes at 2 decimal places for every tick size, so that is the default and
+/// [`OrderBuilder::add_rounding_config`] can override it.
+fn check_size_rounding(
+    size: Decimal,
+    decimals: u32,
+    min_size: Option<Decimal>,
+    max_loss: Option<Decimal>,
+) -> Result<()> {
+    let rounded = size.round_dp_with_strategy(decimals, ToZero);
+    if rounded.is_zero() && !size.is_zero() {
+        return Err(anyhow!(
+            "Size {size} rounds to zero at {decimals} decimal places"
+        ));
+    }
+    if let Some(min_size) = min_size.filter(|&min| rounded < min) {
+        return Err(anyhow!(
+            "Size {size} rounds to {rounded}, below the market's minimum order size {min_size}"
+        ));
+    }
+    if let Some(max_loss) = max_loss {
+        let loss = (size - rounded) / size;
+        if loss > max_loss {
+            return Err(anyhow!(
+                "Rounding size {size} to {rounded} ({decimals} decimal places) changes it by more than {max_loss}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Applies the `max_book_age_ms` guard of `options` to a book stamped at
+/// `book_timestamp_ms`.
+pub(crate) fn check_book_age(
+    book_timestamp_ms: u64,
+    now_ms: u64,
+    options: &CreateOrderOptions,
+) -> Result<()> {
+    if let Some(max_age_ms) = options.max_book_age_ms {
+        let age_ms = now_ms.saturating_sub(book_timestamp_ms);
+        if age_ms > max_age_ms {
+            return Err(anyhow!(
+                "Order book is {age_ms} ms old, more than the allowed {max_age_ms} ms"
+            ));
+        }
+    }
+    Ok(())
+}
+
 impl OrderBuilder {
+    /// Creates a standalone builder. No client, host or HTTP state is needed,
+    /// which makes it suitable for offline signing services.
     pub fn new(
         signer: Box<dyn EthSigner>,
         sig_type: Option<SigType>,
@@ -129,11 +476,56 @@ impl OrderBuilder {
             signer,
             sig_type,
             funder,
+            round_configs: ROUNDING_CONFIG.clone(),
         }
     }
 
+    /// Registers (or overrides) the rounding used for orders on `tick_size`,
+    /// e.g. for tick sizes introduced after this crate was released.
+    pub fn add_rounding_config(&mut self, tick_size: Decimal, round_config: RoundConfig) {
+        self.round_configs.insert(tick_size, round_config);
+    }
+
+    fn get_round_config(&self, tick_size: Option<Decimal>) -> Result<&RoundConfig> {
+        let tick_size = tick_size.context("Cannot create order without tick size")?;
+        self.round_configs
+            .get(&tick_size)
+            .with_context(|| format!("No rounding config for tick size {tick_size}"))
+    }
+
+    /// Maker address for orders built from now on.
+    pub fn set_funder(&mut self, funder: Address) {
+        self.funder = funder;
+    }
+
+    /// Signature type for orders built from now on.
+    pub fn set_sig_type(&mut self, sig_type: SigType) {
+        self.sig_type = sig_type;
+    }
+
+    pub fn sig_type(&self) -> SigType {
+        self.sig_type
+    }
+
     pub fn get_sig_type(&self) -> u8 {
-        self.sig_type as u8
+        self.sig_type.as_u8()
+    }
+
+    pub fn funder(&self) -> Address {
+        self.funder
+    }
+
+    /// Fails if a proxy or safe signature type is paired with the signer's own
+    /// address as funder, which usually means the funder was never set. Not
+    /// checked when building orders; call it to validate a configuration.
+    pub fn check_funder(&self) -> Result<()> {
+        if self.sig_type.requires_funder() && self.funder == self.signer.address() {
+            return Err(anyhow!(
+                "Signature type {:?} needs the proxy or safe address as funder, not the signer",
+                self.sig_type
+            ));
+        }
+        Ok(())
     }
 
     fn fix_amount_rounding(&self, mut amt: Decimal, round_config: &RoundConfig) -> Decimal {
@@ -152,8 +544,14 @@ impl OrderBuilder {
         size: Decimal,
         price: Decimal,
         round_config: &RoundConfig,
+        price_rounding: PriceRounding,
     ) -> (u32, u32) {
-        let raw_price = price.round_dp_with_strategy(round_config.price, MidpointTowardZero);
+        let strategy = match (price_rounding, side) {
+            (PriceRounding::Nearest, _) => MidpointTowardZero,
+            (PriceRounding::Conservative, Side::BUY) => ToZero,
+            (PriceRounding::Conservative, Side::SELL) => AwayFromZero,
+        };
+        let raw_price = price.round_dp_with_strategy(round_config.price, strategy);
 
         match side {
             Side::BUY => {
@@ -207,34 +605,55 @@ impl OrderBuilder {
 
     fn clamp_amount_precision(side: Side, maker: Decimal, taker: Decimal) -> (Decimal, Decimal) {
         match side {
-            // BUY: maker is quote (USD) -> 2dp, taker is base -> 4dp
+            // BUY: maker is quote (USD), taker is base
             Side::BUY => (
-                maker.round_dp_with_strategy(2, MidpointTowardZero),
-                taker.round_dp_with_strategy(4, MidpointTowardZero),
+                maker.round_dp_with_strategy(QUOTE_PRECISION, MidpointTowardZero),
+                taker.round_dp_with_strategy(BASE_PRECISION, MidpointTowardZero),
             ),
-            // SELL: maker is base -> 4dp, taker is quote (USD) -> 2dp
+            // SELL: maker is base, taker is quote (USD)
             Side::SELL => (
-                maker.round_dp_with_strategy(4, MidpointTowardZero),
-                taker.round_dp_with_strategy(2, MidpointTowardZero),
+                maker.round_dp_with_strategy(BASE_PRECISION, MidpointTowardZero),
+                taker.round_dp_with_strategy(QUOTE_PRECISION, MidpointTowardZero),
             ),
         }
     }
+    /// Price a market buy of `amount_to_match` USDC reaches against `positions`,
+    /// the asks of the book in any order.
     pub fn calculate_market_price(
         &self,
         positions: &[OrderSummary],
         amount_to_match: Decimal,
     ) -> Result<Decimal> {
-        let mut sum = Decimal::ZERO;
-
-        for p in positions {
-            sum += p.size * p.price;
-            if sum >= amount_to_match {
-                return Ok(p.price);
+        match self.try_calculate_market_price(positions, Side::BUY, amount_to_match) {
+            MarketPriceFill {
+                price: Some(price),
+                fully_matched: true,
+                ..
+            } => Ok(price),
+            fill => Err(InsufficientLiquidity {
+                requested_amount: amount_to_match,
+                available_amount: fill.filled_amount,
             }
+            .into()),
         }
-        Err(anyhow!(
-            "Not enough liquidity to create market order with amount {amount_to_match}"
-        ))
+    }
+
+    /// Walks `positions` (asks for `BUY`, bids for `SELL`) best price first until
+    /// `amount_to_match` is covered, reporting how much could be matched instead
+    /// of failing when the book is too thin.
+    pub fn try_calculate_market_price(
+        &self,
+        positions: &[OrderSummary],
+        side: Side,
+        amount_to_match: Decimal,
+    ) -> MarketPriceFill {
+        walk_book(
+            positions,
+            side,
+            amount_to_match,
+            |p| p.size * p.price,
+            |_, _| {},
+        )
     }
 
     pub fn create_market_order(
@@ -248,9 +667,7 @@ impl OrderBuilder {
         let (maker_amount, taker_amount) = self.get_market_order_amounts(
             order_args.amount,
             price,
-            &ROUNDING_CONFIG[&options
-                .tick_size
-                .context("Cannot create order without tick size")?],
+            self.get_round_config(options.tick_size)?,
         );
 
         let contract_config = get_contract_config(
@@ -261,14 +678,11 @@ impl OrderBuilder {
         )
         .context("No contract found with given chain_id and neg_risk")?;
 
-        let exchange_address = Address::from_str(contract_config.exchange.as_ref())
-            .context("Invalid exchange address")?;
-
         self.build_signed_order(
             order_args.token_id.clone(),
             Side::BUY,
             chain_id,
-            exchange_address,
+            &contract_config,
             maker_amount,
             taker_amount,
             0,
@@ -284,13 +698,72 @@ impl OrderBuilder {
         extras: &ExtraOrderArgs,
         options: CreateOrderOptions,
     ) -> Result<SignedOrderRequest> {
+        let (maker_amount, taker_amount, contract_config) =
+            self.limit_order_amounts(chain_id, order_args, options)?;
+
+        self.build_signed_order(
+            order_args.token_id.clone(),
+            order_args.side,
+            chain_id,
+            &contract_config,
+            maker_amount,
+            taker_amount,
+            expiration,
+            extras,
+        )
+    }
+
+    /// Same as [`OrderBuilder::create_order`], but stops short of signing. See
+    /// [`UnsignedOrder`].
+    pub fn build_unsigned_order(
+        &self,
+        chain_id: u64,
+        order_args: &OrderArgs,
+        expiration: u64,
+        extras: &ExtraOrderArgs,
+        options: CreateOrderOptions,
+    ) -> Result<UnsignedOrder> {
+        let (maker_amount, taker_amount, contract_config) =
+            self.limit_order_amounts(chain_id, order_args, options)?;
+
+        let order = self.build_order(
+            &order_args.token_id,
+            order_args.side,
+            maker_amount,
+            taker_amount,
+            expiration,
+            extras,
+        )?;
+        let exchange = Address::from_str(contract_config.exchange.as_ref())
+            .context("Invalid exchange address")?;
+        let digest = order_digest(
+            &order,
+            chain_id,
+            exchange,
+            &contract_config.exchange_domain_version,
+        );
+        Ok((order, digest.0, exchange))
+    }
+
+    fn limit_order_amounts(
+        &self,
+        chain_id: u64,
+        order_args: &OrderArgs,
+        options: CreateOrderOptions,
+    ) -> Result<(u32, u32, ContractConfig)> {
+        let round_config = self.get_round_config(options.tick_size)?;
+        check_size_rounding(
+            order_args.size,
+            round_config.size,
+            options.min_order_size,
+            options.max_size_rounding,
+        )?;
         let (maker_amount, taker_amount) = self.get_order_amounts(
             order_args.side,
             order_args.size,
             order_args.price,
-            &ROUNDING_CONFIG[&options
-                .tick_size
-                .context("Cannot create order without tick size")?],
+            round_config,
+            options.price_rounding,
         );
 
         let contract_config = get_contract_config(
@@ -301,71 +774,828 @@ impl OrderBuilder {
         )
         .context("No contract found with given chain_id and neg_risk")?;
 
-        let exchange_address = Address::from_str(contract_config.exchange.as_ref())
-            .context("Invalid exchange address")?;
-
-        self.build_signed_order(
-            order_args.token_id.clone(),
-            order_args.side,
-            chain_id,
-            exchange_address,
-            maker_amount,
-            taker_amount,
-            expiration,
-            extras,
-        )
+        Ok((maker_amount, taker_amount, contract_config))
     }
 
-    #[allow(clippy::too_many_arguments)]
-    fn build_signed_order(
+    fn build_order(
         &self,
-        token_id: String,
+        token_id: &str,
         side: Side,
-        chain_id: u64,
-        exchange: Address,
         maker_amount: u32,
         taker_amount: u32,
         expiration: u64,
         extras: &ExtraOrderArgs,
-    ) -> Result<SignedOrderRequest> {
-        let seed = generate_seed();
-        let taker_address =
-            Address::from_str(extras.taker.as_ref()).context("Invalid taker address")?;
+    ) -> Result<Order> {
+        let seed = extras.salt.unwrap_or_else(generate_seed);
 
-        let u256_token_id =
-            U256::from_str_radix(token_id.as_ref(), 10).context("Incorrect tokenId format")?;
-
-        let order = Order {
+        Ok(Order {
             salt: U256::from(seed),
             maker: self.funder,
             signer: self.signer.address(),
-            taker: taker_address,
-            tokenId: u256_token_id,
+            taker: extras.taker_address(),
+            tokenId: parse_token_id(token_id)?,
             makerAmount: U256::from(maker_amount),
             takerAmount: U256::from(taker_amount),
             expiration: U256::from(expiration),
             nonce: extras.nonce,
-            feeRateBps: U256::from(extras.fee_rate_bps),
+            feeRateBps: U256::from(extras.fee_rate_bps.unwrap_or_default()),
             side: side as u8,
-            signatureType: self.sig_type as u8,
+            signatureType: self.sig_type.as_u8(),
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn build_signed_order(
+        &self,
+        token_id: String,
+        side: Side,
+        chain_id: u64,
+        contract_config: &ContractConfig,
+        maker_amount: u32,
+        taker_amount: u32,
+        expiration: u64,
+        extras: &ExtraOrderArgs,
+    ) -> Result<SignedOrderRequest> {
+        let order = self.build_order(
+            &token_id,
+            side,
+            maker_amount,
+            taker_amount,
+            expiration,
+            extras,
+        )?;
+
+        let exchange = Address::from_str(contract_config.exchange.as_ref())
+            .context("Invalid exchange address")?;
+        let signature = sign_order_message(
+            &self.signer,
+            order.clone(),
+            chain_id,
+            exchange,
+            &contract_config.exchange_domain_version,
+        )?;
+
+        signed_order_request(&order, token_id, signature)
+    }
+}
+
+/// An order ready to be signed elsewhere: the EIP-712 struct, its signing
+/// digest and the exchange contract it is bound to.
+pub type UnsignedOrder = (Order, [u8; 32], Address);
+
+/// Assembles the request for an order built with
+/// [`OrderBuilder::build_unsigned_order`] from an externally produced signature.
+/// The signature may use either `v` encoding and must recover to the order's
+/// signer.
+pub fn finalize_order(unsigned: &UnsignedOrder, signature: &str) -> Result<SignedOrderRequest> {
+    let (order, digest, _) = unsigned;
+    let signature = SignatureParts::from_hex(signature)?
+        .normalized(RecoveryIdFormat::Electrum)?
+        .to_hex();
+
+    let recovered = PrimitiveSignature::from_str(&signature)
+        .context("Invalid order signature")?
+        .recover_address_from_prehash(&B256::from(*digest))
+        .context("Could not recover order signer")?;
+    if recovered != order.signer {
+        return Err(anyhow!(
+            "Signature recovers to {recovered}, but the order signer is {}",
+            order.signer
+        ));
+    }
+
+    signed_order_request(order, token_id_to_decimal_string(order.tokenId), signature)
+}
+
+fn signed_order_request(
+    order: &Order,
+    token_id: String,
+    signature: String,
+) -> Result<SignedOrderRequest> {
+    let side = match order.side {
+        0 => Side::BUY,
+        1 => Side::SELL,
+        s => return Err(anyhow!("Invalid order side {s}")),
+    };
+    Ok(SignedOrderRequest {
+        salt: u64::try_from(order.salt).context("Order salt does not fit in u64")?,
+        maker: order.maker.to_checksum(None),
+        signer: order.signer.to_checksum(None),
+        taker: order.taker.to_checksum(None),
+        token_id,
+        maker_amount: order.makerAmount.to_string(),
+        taker_amount: order.takerAmount.to_string(),
+        expiration: order.expiration.to_string(),
+        nonce: order.nonce.to_string(),
+        fee_rate_bps: order.feeRateBps.to_string(),
+        side: side.as_str().into(),
+        signature_type: order.signatureType,
+        signature,
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use alloy_signer::SignerSync;
+    use alloy_signer_local::PrivateKeySigner;
+
+    const TEST_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+    const TOKEN_ID: &str =
+        "71321045679252212594626385532706912750332728571942532289631379312455583992563";
+
+    pub(crate) fn test_signer() -> Box<dyn EthSigner> {
+        Box::new(TEST_KEY.parse::<PrivateKeySigner>().unwrap())
+    }
+
+    /// Signs `args` offline with [`test_signer`] on Polygon, tick size 0.01.
+    pub(crate) fn sign_test_order(
+        args: &OrderArgs,
+        extras: Option<ExtraOrderArgs>,
+    ) -> SignedOrderRequest {
+        let options = CreateOrderOptions {
+            tick_size: Some(Decimal::new(1, 2)),
+            neg_risk: Some(false),
+            ..Default::default()
         };
+        sign_order(test_signer(), 137, args, options, extras).unwrap()
+    }
 
-        let signature = sign_order_message(&self.signer, order, chain_id, exchange)?;
-
-        Ok(SignedOrderRequest {
-            salt: seed,
-            maker: self.funder.to_checksum(None),
-            signer: self.signer.address().to_checksum(None),
-            taker: taker_address.to_checksum(None),
-            token_id,
-            maker_amount: maker_amount.to_string(),
-            taker_amount: taker_amount.to_string(),
-            expiration: expiration.to_string(),
-            nonce: extras.nonce.to_string(),
-            fee_rate_bps: extras.fee_rate_bps.to_string(),
-            side: side.as_str().into(),
-            signature_type: self.sig_type as u8,
-            signature,
-        })
+    #[test]
+    fn test_amount_precision_constants() {
+        let usdc = Decimal::from_str("12.345678").unwrap();
+        assert_eq!(decimal_to_token_u32(usdc), 12_345_678);
+        assert_eq!(
+            Decimal::from(decimal_to_token_u32(Decimal::ONE)),
+            Decimal::from(10u64.pow(USDC_DECIMALS))
+        );
+
+        let (maker, taker) = OrderBuilder::clamp_amount_precision(
+            Side::BUY,
+            Decimal::from_str("1.23456").unwrap(),
+            Decimal::from_str("2.3456789").unwrap(),
+        );
+        assert_eq!(maker.scale(), QUOTE_PRECISION);
+        assert_eq!(taker.scale(), BASE_PRECISION);
+        assert_eq!(maker, Decimal::from_str("1.23").unwrap());
+        assert_eq!(taker, Decimal::from_str("2.3457").unwrap());
+
+        let (maker, taker) = OrderBuilder::clamp_amount_precision(
+            Side::SELL,
+            Decimal::from_str("2.3456789").unwrap(),
+            Decimal::from_str("1.23456").unwrap(),
+        );
+        assert_eq!(maker.scale(), BASE_PRECISION);
+        assert_eq!(taker.scale(), QUOTE_PRECISION);
+    }
+
+    fn test_options() -> CreateOrderOptions {
+        CreateOrderOptions {
+            tick_size: Some(Decimal::from_str("0.01").unwrap()),
+            neg_risk: Some(false),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_sign_order_without_client() {
+        let signer = test_signer();
+        let address = signer.address().to_checksum(None);
+        let args = OrderArgs::new(
+            TOKEN_ID,
+            Decimal::from_str("0.5").unwrap(),
+            Decimal::from(10),
+            Side::BUY,
+        );
+
+        let order = sign_order(signer, 137, &args, test_options(), None).unwrap();
+
+        assert_eq!(order.maker, address);
+        assert_eq!(order.signer, address);
+        assert_eq!(order.token_id, TOKEN_ID);
+        assert_eq!(order.maker_amount, "5000000");
+        assert_eq!(order.taker_amount, "10000000");
+        assert_eq!(order.side, "BUY");
+        assert!(order.signature.starts_with("0x"));
+    }
+
+    #[test]
+    fn test_gnosis_safe_orders() {
+        use alloy_primitives::{PrimitiveSignature, B256};
+
+        let owner = test_signer().address();
+        let safe = Address::from_str("0x0000000000000000000000000000000000005afe").unwrap();
+        let args = OrderArgs::new(
+            TOKEN_ID,
+            Decimal::from_str("0.5").unwrap(),
+            Decimal::from(10),
+            Side::BUY,
+        );
+
+        let builder = OrderBuilder::new(test_signer(), Some(SigType::GnosisSafe), Some(safe));
+        assert_eq!(builder.get_sig_type(), 3);
+        assert!(builder.check_funder().is_ok());
+        let order = builder
+            .create_order(137, &args, 0, &ExtraOrderArgs::default(), test_options())
+            .unwrap();
+
+        assert_eq!(order.signature_type, 3);
+        assert_eq!(order.maker, safe.to_checksum(None));
+        assert_eq!(order.signer, owner.to_checksum(None));
+        let exchange = get_contract_config(137, false).unwrap().exchange;
+        let hash = crate::order_hash(&order, 137, Address::from_str(&exchange).unwrap()).unwrap();
+        let signature = PrimitiveSignature::from_str(&order.signature).unwrap();
+        let recovered = signature
+            .recover_address_from_prehash(&B256::from_str(&hash).unwrap())
+            .unwrap();
+        assert_eq!(recovered, owner);
+
+        // A missing funder is only reported when asked for.
+        let builder = OrderBuilder::new(test_signer(), Some(SigType::GnosisSafe), None);
+        let err = builder.check_funder().unwrap_err();
+        assert!(err.to_string().contains("needs the proxy or safe address"));
+        assert!(builder
+            .create_order(137, &args, 0, &ExtraOrderArgs::default(), test_options())
+            .is_ok());
+    }
+
+    #[test]
+    fn test_sig_types_are_distinct_on_the_wire() {
+        let sig_types = [
+            SigType::Eoa,
+            SigType::EmailOrMagic,
+            SigType::BrowserWalletProxy,
+            SigType::GnosisSafe,
+        ];
+        let values: Vec<_> = sig_types.iter().map(|t| t.as_u8()).collect();
+        assert_eq!(values, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_size_rounding_checks() {
+        let builder = OrderBuilder::new(test_signer(), None, None);
+        let order = |size: &str, max_size_rounding: Option<&str>| {
+            let args = OrderArgs::new(
+                TOKEN_ID,
+                Decimal::from_str("0.5").unwrap(),
+                Decimal::from_str(size).unwrap(),
+                Side::BUY,
+            );
+            let options = CreateOrderOptions {
+                max_size_rounding: max_size_rounding.map(|m| Decimal::from_str(m).unwrap()),
+                ..test_options()
+            };
+            builder.create_order(137, &args, 0, &ExtraOrderArgs::default(), options)
+        };
+
+        let err = order("0.004", None).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("rounds to zero at 2 decimal places"));
+
+        // 1.019 -> 1.01 loses ~0.9%.
+        assert_eq!(order("1.019", None).unwrap().taker_amount, "1010000");
+        assert!(order("1.019", Some("0.01")).is_ok());
+        let err = order("1.019", Some("0.005")).unwrap_err();
+        assert!(err.to_string().contains("changes it by more than 0.005"));
+        assert!(order("1.01", Some("0")).is_ok());
+    }
+
+    #[test]
+    fn test_size_rounding_respects_min_order_size() {
+        let builder = OrderBuilder::new(test_signer(), None, None);
+        let order = |size: &str| {
+            let args = OrderArgs::new(
+                TOKEN_ID,
+                Decimal::from_str("0.5").unwrap(),
+                Decimal::from_str(size).unwrap(),
+                Side::BUY,
+            );
+            let options = CreateOrderOptions {
+                min_order_size: Some(Decimal::from(15)),
+                ..test_options()
+            };
+            builder.create_order(137, &args, 0, &ExtraOrderArgs::default(), options)
+        };
+
+        // 15.004 keeps the minimum after rounding, 14.999 drops below it.
+        assert_eq!(order("15.004").unwrap().taker_amount, "15000000");
+        let err = order("14.999").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("below the market's minimum order size 15"));
+    }
+
+    #[test]
+    fn test_size_precision_follows_round_config() {
+        let mut builder = OrderBuilder::new(test_signer(), None, None);
+        let tick_size = Decimal::from_str("0.01").unwrap();
+        builder.add_rounding_config(tick_size, RoundConfig::new(2, 4, 6));
+        let args = OrderArgs::new(
+            TOKEN_ID,
+            Decimal::from_str("0.5").unwrap(),
+            Decimal::from_str("1.0125").unwrap(),
+            Side::BUY,
+        );
+        let options = CreateOrderOptions {
+            max_size_rounding: Some(Decimal::ZERO),
+            ..test_options()
+        };
+
+        let order = builder
+            .create_order(137, &args, 0, &ExtraOrderArgs::default(), options)
+            .unwrap();
+        assert_eq!(order.taker_amount, "1012500");
+    }
+
+    #[test]
+    fn test_conservative_price_rounding() {
+        let builder = OrderBuilder::new(test_signer(), None, None);
+        let conservative = CreateOrderOptions {
+            price_rounding: PriceRounding::Conservative,
+            ..test_options()
+        };
+        let quote_amount = |price: &str, side: Side, options: CreateOrderOptions| {
+            let args = OrderArgs::new(
+                TOKEN_ID,
+                Decimal::from_str(price).unwrap(),
+                Decimal::from(10),
+                side,
+            );
+            let order = builder
+                .create_order(137, &args, 0, &ExtraOrderArgs::default(), options)
+                .unwrap();
+            match side {
+                Side::BUY => order.maker_amount,
+                Side::SELL => order.taker_amount,
+            }
+        };
+
+        // Halfway between ticks the default rounds an ask down, giving it away.
+        assert_eq!(quote_amount("0.505", Side::SELL, test_options()), "5000000");
+        assert_eq!(
+            quote_amount("0.505", Side::SELL, conservative.clone()),
+            "5100000"
+        );
+        // Above halfway the default rounds a bid up, paying more than asked.
+        assert_eq!(quote_amount("0.507", Side::BUY, test_options()), "5100000");
+        assert_eq!(
+            quote_amount("0.507", Side::BUY, conservative.clone()),
+            "5000000"
+        );
+        // On the grid both agree.
+        assert_eq!(
+            quote_amount("0.5", Side::BUY, test_options()),
+            quote_amount("0.5", Side::BUY, conservative)
+        );
+    }
+
+    #[test]
+    fn test_fixed_salt_gives_identical_orders() {
+        let builder = OrderBuilder::new(test_signer(), None, None);
+        let args = OrderArgs::new(
+            TOKEN_ID,
+            Decimal::from_str("0.5").unwrap(),
+            Decimal::from(10),
+            Side::BUY,
+        );
+        let extras = ExtraOrderArgs::default().with_salt(42);
+        let exchange =
+            Address::from_str(&get_contract_config(137, false).unwrap().exchange).unwrap();
+
+        let first = builder
+            .create_order(137, &args, 0, &extras, test_options())
+            .unwrap();
+        let retry = builder
+            .create_order(137, &args, 0, &extras, test_options())
+            .unwrap();
+
+        assert_eq!(first.salt, 42);
+        assert_eq!(
+            crate::order_hash(&first, 137, exchange).unwrap(),
+            crate::order_hash(&retry, 137, exchange).unwrap()
+        );
+        assert_eq!(first.signature, retry.signature);
+    }
+
+    #[test]
+    fn test_unsigned_order_split_and_finalize() {
+        let builder = OrderBuilder::new(test_signer(), None, None);
+        let args = OrderArgs::new(
+            TOKEN_ID,
+            Decimal::from_str("0.5").unwrap(),
+            Decimal::from(10),
+            Side::SELL,
+        );
+        let extras = ExtraOrderArgs::default().with_salt(7);
+        let expected = builder
+            .create_order(137, &args, 0, &extras, test_options())
+            .unwrap();
+
+        let unsigned = builder
+            .build_unsigned_order(137, &args, 0, &extras, test_options())
+            .unwrap();
+        let (order, digest, exchange) = &unsigned;
+        assert_eq!(order.signer, test_signer().address());
+        assert_eq!(
+            crate::order_hash(&expected, 137, *exchange).unwrap(),
+            alloy_primitives::hex::encode_prefixed(digest)
+        );
+
+        // Stand-in for the remote signing service.
+        let signature = test_signer().sign_hash_sync(&B256::from(*digest)).unwrap();
+        let electrum = alloy_primitives::hex::encode_prefixed(signature.as_bytes());
+        let finalized = finalize_order(&unsigned, &electrum).unwrap();
+        assert_eq!(
+            serde_json::to_value(&finalized).unwrap(),
+            serde_json::to_value(&expected).unwrap()
+        );
+
+        let parity = SignatureParts::from_hex(&electrum)
+            .unwrap()
+            .normalized(RecoveryIdFormat::Parity)
+            .unwrap();
+        let finalized = finalize_order(&unsigned, &parity.to_hex()).unwrap();
+        assert_eq!(finalized.signature, expected.signature);
+
+        let other = PrivateKeySigner::random()
+            .sign_hash_sync(&B256::from(*digest))
+            .unwrap();
+        let err = finalize_order(
+            &unsigned,
+            &alloy_primitives::hex::encode_prefixed(other.as_bytes()),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("order signer"));
+    }
+
+    #[test]
+    fn test_improved_price() {
+        let d = |s: &str| Decimal::from_str(s).unwrap();
+        let tick = d("0.01");
+
+        assert_eq!(
+            improved_price(d("0.45"), None, tick, Side::BUY),
+            Some(d("0.46"))
+        );
+        assert_eq!(
+            improved_price(d("0.55"), None, tick, Side::SELL),
+            Some(d("0.54"))
+        );
+        // Off grid best prices still end up strictly better and on the grid.
+        assert_eq!(
+            improved_price(d("0.455"), None, tick, Side::BUY),
+            Some(d("0.46"))
+        );
+        assert_eq!(
+            improved_price(d("0.545"), None, tick, Side::SELL),
+            Some(d("0.54"))
+        );
+        // Already at the price limits.
+        assert_eq!(improved_price(d("0.99"), None, tick, Side::BUY), None);
+        assert_eq!(improved_price(d("0.01"), None, tick, Side::SELL), None);
+        // One tick wide spread: stepping in front would trade.
+        assert_eq!(
+            improved_price(d("0.45"), Some(d("0.46")), tick, Side::BUY),
+            None
+        );
+        assert_eq!(
+            improved_price(d("0.46"), Some(d("0.45")), tick, Side::SELL),
+            None
+        );
+    }
+
+    #[test]
+    fn test_snap_to_tick() {
+        let d = |s: &str| Decimal::from_str(s).unwrap();
+        assert_eq!(
+            snap_to_tick(d("0.537"), d("0.01"), Side::BUY).unwrap(),
+            d("0.53")
+        );
+        assert_eq!(
+            snap_to_tick(d("0.537"), d("0.01"), Side::SELL).unwrap(),
+            d("0.54")
+        );
+        assert_eq!(
+            snap_to_tick(d("0.54"), d("0.01"), Side::SELL).unwrap(),
+            d("0.54")
+        );
+        assert_eq!(
+            snap_to_tick(d("0.5375"), d("0.001"), Side::BUY).unwrap(),
+            d("0.537")
+        );
+        assert_eq!(
+            snap_to_tick(d("0.004"), d("0.01"), Side::BUY).unwrap(),
+            d("0.01")
+        );
+        assert_eq!(
+            snap_to_tick(d("0.995"), d("0.01"), Side::SELL).unwrap(),
+            d("0.99")
+        );
+        assert!(snap_to_tick(d("0.5"), Decimal::ZERO, Side::BUY).is_err());
+    }
+
+    #[test]
+    fn test_custom_rounding_config() {
+        let tick_size = Decimal::from_str("0.005").unwrap();
+        let args = OrderArgs::new(
+            TOKEN_ID,
+            Decimal::from_str("0.125").unwrap(),
+            Decimal::from(10),
+            Side::SELL,
+        );
+        let options = || CreateOrderOptions {
+            tick_size: Some(tick_size),
+            neg_risk: Some(false),
+            ..Default::default()
+        };
+        let extras = ExtraOrderArgs::default();
+
+        let mut builder = OrderBuilder::new(test_signer(), None, None);
+        assert!(builder
+            .create_order(137, &args, 0, &extras, options())
+            .is_err());
+
+        builder.add_rounding_config(tick_size, RoundConfig::new(3, 2, 5));
+        let order = builder
+            .create_order(137, &args, 0, &extras, options())
+            .unwrap();
+
+        assert_eq!(order.maker_amount, "10000000");
+        assert_eq!(order.taker_amount, "1250000");
+    }
+
+    #[test]
+    fn test_price_below_min() {
+        let tick_size = Decimal::from_str("0.01").unwrap();
+        let err = check_price_in_range(Decimal::from_str("0.001").unwrap(), tick_size).unwrap_err();
+
+        assert_eq!(err.violated, PriceBound::Min);
+        assert_eq!(err.min_price, tick_size);
+        assert_eq!(err.max_price, Decimal::from_str("0.99").unwrap());
+    }
+
+    #[test]
+    fn test_price_above_max() {
+        let tick_size = Decimal::from_str("0.01").unwrap();
+        let err = check_price_in_range(Decimal::from_str("0.995").unwrap(), tick_size).unwrap_err();
+
+        assert_eq!(err.violated, PriceBound::Max);
+        assert!(err.to_string().contains("above the maximum price 0.99"));
+        assert!(check_price_in_range(Decimal::from_str("0.99").unwrap(), tick_size).is_ok());
+    }
+
+    fn level(price: &str, size: &str) -> OrderSummary {
+        OrderSummary {
+            price: Decimal::from_str(price).unwrap(),
+            size: Decimal::from_str(size).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_market_price_partial_fill() {
+        let builder = OrderBuilder::new(test_signer(), None, None);
+        let asks = vec![level("0.5", "10"), level("0.6", "10")];
+
+        let fill = builder.try_calculate_market_price(&asks, Side::BUY, Decimal::from(20));
+        assert_eq!(
+            fill,
+            MarketPriceFill {
+                price: Some(Decimal::from_str("0.6").unwrap()),
+                filled_amount: Decimal::from(11),
+                fully_matched: false,
+            }
+        );
+        let err = builder
+            .calculate_market_price(&asks, Decimal::from(20))
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<InsufficientLiquidity>(),
+            Some(&InsufficientLiquidity {
+                requested_amount: Decimal::from(20),
+                available_amount: Decimal::from(11),
+            })
+        );
+
+        let fill = builder.try_calculate_market_price(&asks, Side::BUY, Decimal::from(8));
+        assert_eq!(fill.price, Some(Decimal::from_str("0.6").unwrap()));
+        assert_eq!(fill.filled_amount, Decimal::from(8));
+        assert!(fill.fully_matched);
+    }
+
+    #[test]
+    fn test_executable_price() {
+        let asks = vec![level("0.5", "10"), level("0.6", "10")];
+
+        assert_eq!(
+            executable_price(&asks, Side::BUY, Decimal::from(10)),
+            Some(Decimal::from_str("0.5").unwrap())
+        );
+        assert_eq!(
+            executable_price(&asks, Side::BUY, Decimal::from(15)),
+            Some(Decimal::from_str("0.6").unwrap())
+        );
+        assert_eq!(
+            executable_price(&asks, Side::BUY, Decimal::from(20)),
+            Some(Decimal::from_str("0.6").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_vwap() {
+        let asks = vec![level("0.5", "10"), level("0.6", "10"), level("0.8", "5")];
+
+        // Within the first level the average is just its price.
+        assert_eq!(
+            vwap(&asks, Side::BUY, Decimal::from(4)),
+            Some(Decimal::from_str("0.5").unwrap())
+        );
+        // 5 USDC buys 10 @ 0.5, the next 3 buy 5 @ 0.6: 8 / 15.
+        assert_eq!(
+            vwap(&asks, Side::BUY, Decimal::from(8)),
+            Some(Decimal::from(8) / Decimal::from(15))
+        );
+        // All three levels: 15 USDC for 25 shares.
+        assert_eq!(
+            vwap(&asks, Side::BUY, Decimal::from(15)),
+            Some(Decimal::from_str("0.6").unwrap())
+        );
+        assert_eq!(
+            vwap(&asks, Side::BUY, Decimal::from_str("15.01").unwrap()),
+            None
+        );
+        assert_eq!(vwap(&asks, Side::BUY, Decimal::ZERO), None);
+        assert_eq!(vwap(&[], Side::BUY, Decimal::ONE), None);
+    }
+
+    #[test]
+    fn test_vwap_worst_first_book() {
+        let asks = vec![level("0.8", "5"), level("0.6", "10"), level("0.5", "10")];
+        let bids = vec![level("0.2", "10"), level("0.4", "10")];
+
+        // Same fills as test_vwap: 10 @ 0.5, then 5 @ 0.6.
+        assert_eq!(
+            vwap(&asks, Side::BUY, Decimal::from(8)),
+            Some(Decimal::from(8) / Decimal::from(15))
+        );
+        // 4 USDC comes entirely from the 0.4 bid.
+        assert_eq!(
+            vwap(&bids, Side::SELL, Decimal::from(4)),
+            Some(Decimal::from_str("0.4").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_executable_price_insufficient_depth() {
+        let bids = vec![level("0.5", "10"), level("0.4", "5")];
+
+        assert_eq!(executable_price(&bids, Side::SELL, Decimal::from(16)), None);
+        assert_eq!(executable_price(&[], Side::SELL, Decimal::ONE), None);
+    }
+
+    #[test]
+    fn test_walk_worst_first_book() {
+        // `/book` order: asks descending, bids ascending.
+        let asks = vec![level("0.7", "10"), level("0.6", "10"), level("0.5", "10")];
+        let bids = vec![level("0.3", "10"), level("0.4", "10"), level("0.45", "10")];
+
+        assert_eq!(
+            executable_price(&asks, Side::BUY, Decimal::from(15)),
+            Some(Decimal::from_str("0.6").unwrap())
+        );
+        assert_eq!(
+            executable_price(&bids, Side::SELL, Decimal::from(15)),
+            Some(Decimal::from_str("0.4").unwrap())
+        );
+
+        let builder = OrderBuilder::new(test_signer(), None, None);
+        assert_eq!(
+            builder
+                .calculate_market_price(&asks, Decimal::from(5))
+                .unwrap(),
+            Decimal::from_str("0.5").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_market_order_level_guard() {
+        let asks = vec![level("0.5", "10"), level("0.6", "10"), level("0.7", "10")];
+        let options = CreateOrderOptions {
+            max_book_levels: Some(2),
+            ..Default::default()
+        };
+        let price = Decimal::from_str("0.6").unwrap();
+
+        assert!(
+            check_market_order_liquidity(&asks, Decimal::from(10), price, None, &options).is_ok()
+        );
+        assert!(
+            check_market_order_liquidity(&asks, Decimal::from(12), price, None, &options).is_err()
+        );
+    }
+
+    #[test]
+    fn test_market_order_level_guard_worst_first() {
+        // `/book` lists asks worst price first; the fill still starts at 0.5.
+        let asks = vec![level("0.9", "100"), level("0.5", "10")];
+        let options = CreateOrderOptions {
+            max_book_levels: Some(1),
+            ..Default::default()
+        };
+        let price = Decimal::from_str("0.9").unwrap();
+
+        assert!(
+            check_market_order_liquidity(&asks, Decimal::from(5), price, None, &options).is_ok()
+        );
+        assert!(
+            check_market_order_liquidity(&asks, Decimal::from(10), price, None, &options).is_err()
+        );
+    }
+
+    #[test]
+    fn test_price_through_book_guard() {
+        let book = OrderBookSummary {
+            market: "0xcond".into(),
+            asset_id: TOKEN_ID.into(),
+            hash: String::new(),
+            timestamp: 0,
+            bids: vec![level("0.38", "10"), level("0.4", "10")],
+            asks: vec![level("0.55", "10"), level("0.5", "10")],
+            min_order_size: None,
+            tick_size: None,
+            neg_risk: None,
+        };
+        let order = |price: &str, side| {
+            OrderArgs::new(
+                TOKEN_ID,
+                Decimal::from_str(price).unwrap(),
+                Decimal::TEN,
+                side,
+            )
+        };
+
+        // Resting and marginally crossing orders pass.
+        assert!(check_price_through_book(&order("0.45", Side::BUY), &book, 500).is_ok());
+        assert!(check_price_through_book(&order("0.52", Side::BUY), &book, 500).is_ok());
+        assert!(check_price_through_book(&order("0.39", Side::SELL), &book, 500).is_ok());
+
+        // A bid typed as 0.95 instead of 0.49 is 90% through the best ask.
+        let err = check_price_through_book(&order("0.95", Side::BUY), &book, 500).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("9000 bps through the best opposite quote 0.5"));
+        let err = check_price_through_book(&order("0.2", Side::SELL), &book, 500).unwrap_err();
+        assert!(err.to_string().contains("5000 bps"), "{err}");
+
+        let empty = OrderBookSummary {
+            asks: Vec::new(),
+            ..book
+        };
+        assert!(check_price_through_book(&order("0.95", Side::BUY), &empty, 500).is_ok());
+    }
+
+    #[test]
+    fn test_market_order_slippage_guard() {
+        let asks = vec![level("0.5", "10"), level("0.6", "10")];
+        let options = CreateOrderOptions {
+            max_slippage_bps: Some(1000),
+            ..Default::default()
+        };
+        let mid = Some(Decimal::from_str("0.5").unwrap());
+
+        let ok = check_market_order_liquidity(
+            &asks,
+            Decimal::from(5),
+            Decimal::from_str("0.5").unwrap(),
+            mid,
+            &options,
+        );
+        assert!(ok.is_ok());
+
+        let err = check_market_order_liquidity(
+            &asks,
+            Decimal::from(10),
+            Decimal::from_str("0.6").unwrap(),
+            mid,
+            &options,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("2000 bps"));
+    }
+
+    #[test]
+    fn test_sign_order_requires_options() {
+        let args = OrderArgs::new(
+            TOKEN_ID,
+            Decimal::from_str("0.5").unwrap(),
+            Decimal::ONE,
+            Side::BUY,
+        );
+        let options = CreateOrderOptions {
+            neg_risk: None,
+            ..test_options()
+        };
+
+        assert!(sign_order(test_signer(), 137, &args, options, None).is_err());
     }
 }
@@ -0,0 +1,2193 @@
+use crate::*;
+use futures_util::StreamExt;
+use reqwest::ResponseBuilderExt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[test]
+fn test_http_config_wiring() {
+    let config = HttpConfig::default()
+        .with_pool_max_idle_per_host(8)
+        .with_pool_idle_timeout(Some(Duration::from_secs(300)));
+    let client = ClobClient::new("https://clob.polymarket.com").with_http_config(config);
+
+    assert_eq!(client.http_config().pool_max_idle_per_host, 8);
+    assert_eq!(
+        client.http_config().pool_idle_timeout,
+        Some(Duration::from_secs(300))
+    );
+    assert_eq!(
+        ClobClient::new("https://clob.polymarket.com").http_config(),
+        &HttpConfig::default()
+    );
+}
+
+const TEST_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+fn test_creds() -> ApiCreds {
+    ApiCreds {
+        api_key: "key".into(),
+        secret: "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=".into(),
+        passphrase: "pass".into(),
+    }
+}
+
+#[test]
+fn test_data_api_request_is_l2_signed() {
+    let client = ClobClient::with_l2_headers("https://clob.example", TEST_KEY, 137, test_creds())
+        .with_data_api_host("https://data.example");
+
+    let req = client
+        .create_data_api_l2_request(reqwest::Method::GET, "/positions")
+        .unwrap()
+        .build()
+        .unwrap();
+
+    assert_eq!(req.url().as_str(), "https://data.example/positions");
+    for header in [
+        "poly_address",
+        "poly_signature",
+        "poly_timestamp",
+        "poly_api_key",
+        "poly_passphrase",
+    ] {
+        assert!(req.headers().contains_key(header), "missing {header}");
+    }
+    assert_eq!(req.headers()["poly_api_key"], "key");
+}
+
+/// Scripted transport: replies with queued bodies matched by URL path and
+/// records every request URL it sees.
+#[derive(Clone, Default)]
+struct MockTransport {
+    responses: Arc<Mutex<Vec<(String, u16, String)>>>,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+#[derive(Clone, Debug)]
+struct RecordedRequest {
+    url: String,
+    headers: reqwest::header::HeaderMap,
+    body: Option<String>,
+}
+
+impl MockTransport {
+    fn reply(self, path: &str, body: serde_json::Value) -> Self {
+        self.reply_with_status(path, 200, &body.to_string())
+    }
+
+    fn reply_with_status(self, path: &str, status: u16, body: &str) -> Self {
+        self.responses
+            .lock()
+            .unwrap()
+            .push((path.to_owned(), status, body.to_owned()));
+        self
+    }
+
+    fn requests(&self) -> Vec<String> {
+        self.recorded().into_iter().map(|r| r.url).collect()
+    }
+
+    fn recorded(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Transport for MockTransport {
+    fn send(&self, request: reqwest::Request) -> TransportFuture<'_> {
+        self.requests.lock().unwrap().push(RecordedRequest {
+            url: request.url().to_string(),
+            headers: request.headers().clone(),
+            body: request
+                .body()
+                .and_then(|b| b.as_bytes())
+                .map(|b| String::from_utf8_lossy(b).into_owned()),
+        });
+        let mut responses = self.responses.lock().unwrap();
+        let idx = responses
+            .iter()
+            .position(|(path, _, _)| path == request.url().path())
+            .unwrap_or_else(|| panic!("no scripted response for {}", request.url()));
+        let (_, status, body) = responses.remove(idx);
+        let url = request.url().clone();
+        Box::pin(async move {
+            // Let concurrent callers interleave as they would on a real network.
+            tokio::task::yield_now().await;
+            let resp = http::Response::builder()
+                .status(status)
+                .url(url)
+                .body(body)?;
+            Ok(reqwest::Response::from(resp))
+        })
+    }
+}
+
+fn open_order_json(id: &str) -> serde_json::Value {
+    serde_json::json!({
+        "associate_trades": [],
+        "id": id,
+        "status": "LIVE",
+        "market": "0xmarket",
+        "original_size": "10",
+        "outcome": "Yes",
+        "maker_address": "0x0000000000000000000000000000000000000001",
+        "owner": "key",
+        "price": "0.5",
+        "side": "BUY",
+        "size_matched": "0",
+        "asset_id": "123",
+        "expiration": "0",
+        "type": "GTC",
+        "created_at": 0
+    })
+}
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+fn assert_send<T: Send>(_: &T) {}
+
+#[test]
+fn test_client_is_send_sync() {
+    assert_send_sync::<ClobClient>();
+    assert_send_sync::<Box<dyn EthSigner>>();
+    assert_send_sync::<Arc<dyn Transport>>();
+    assert_send_sync::<Arc<dyn Middleware>>();
+
+    let client = ClobClient::with_l2_headers("https://clob.example", TEST_KEY, 137, test_creds());
+    let args = OrderArgs::new("1", Decimal::new(5, 1), Decimal::TEN, Side::BUY);
+    assert_send(&client.get_orders(None, None));
+    assert_send(&client.create_order(&args, None, None, None));
+    assert_send(&client.stream_simplified_markets());
+}
+
+#[tokio::test]
+async fn test_client_shared_across_tasks() {
+    let mut transport = MockTransport::default();
+    for _ in 0..4 {
+        transport = transport.reply("/midpoint", serde_json::json!({"mid": "0.5"}));
+    }
+    let client = Arc::new(ClobClient::new("https://clob.example").with_transport(transport));
+
+    let tasks: Vec<_> = (0..4)
+        .map(|i| {
+            let client = client.clone();
+            tokio::spawn(async move { client.get_midpoint(&i.to_string()).await })
+        })
+        .collect();
+    for task in tasks {
+        assert_eq!(task.await.unwrap().unwrap().mid, Some(Decimal::new(5, 1)));
+    }
+}
+
+#[tokio::test]
+async fn test_get_orders_follows_cursor_through_transport() {
+    let transport = MockTransport::default()
+        .reply(
+            "/data/orders",
+            serde_json::json!({"next_cursor": "MTAw", "data": [open_order_json("a")]}),
+        )
+        .reply(
+            "/data/orders",
+            serde_json::json!({"next_cursor": "LTE=", "data": [open_order_json("b")]}),
+        );
+    let client = ClobClient::with_l2_headers("https://clob.example", TEST_KEY, 137, test_creds())
+        .with_transport(transport.clone());
+
+    let orders = client.get_orders(None, None).await.unwrap();
+
+    let ids: Vec<_> = orders.iter().map(|o| o.id.as_str()).collect();
+    assert_eq!(ids, ["a", "b"]);
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].ends_with("next_cursor=MA%3D%3D"));
+    assert!(requests[1].ends_with("next_cursor=MTAw"));
+}
+
+#[tokio::test]
+async fn test_pagination_stats() {
+    let transport = MockTransport::default()
+        .reply(
+            "/data/orders",
+            serde_json::json!({"next_cursor": "MTAw", "data": [open_order_json("a"), open_order_json("b")]}),
+        )
+        .reply(
+            "/data/orders",
+            serde_json::json!({"next_cursor": "LTE=", "data": [open_order_json("c")]}),
+        )
+        .reply(
+            "/data/trades",
+            serde_json::json!({"next_cursor": "LTE=", "data": [trade_json("t")]}),
+        );
+    let client = ClobClient::with_l2_headers("https://clob.example", TEST_KEY, 137, test_creds())
+        .with_transport(transport.clone());
+
+    let (orders, stats) = client.get_orders_with_stats(None, None).await.unwrap();
+    assert_eq!(orders.len(), 3);
+    assert_eq!(
+        stats,
+        PaginationStats {
+            pages: 2,
+            total_items: 3,
+            last_cursor: Some("LTE=".into()),
+        }
+    );
+
+    let (trades, stats) = client
+        .get_trades_with_stats(None, Some("MTAw"))
+        .await
+        .unwrap();
+    assert_eq!(trades.len(), 1);
+    assert_eq!((stats.pages, stats.total_items), (1, 1));
+    assert!(transport.requests()[2].ends_with("next_cursor=MTAw"));
+}
+
+#[tokio::test]
+async fn test_get_trades_stops_on_repeated_cursor() {
+    let transport = MockTransport::default()
+        .reply(
+            "/data/trades",
+            serde_json::json!({"next_cursor": "MTAw", "data": []}),
+        )
+        .reply(
+            "/data/trades",
+            serde_json::json!({"next_cursor": "MA==", "data": []}),
+        );
+    let client = ClobClient::with_l2_headers("https://clob.example", TEST_KEY, 137, test_creds())
+        .with_transport(transport.clone());
+
+    let trades = client.get_trades(None, None).await.unwrap();
+
+    assert!(trades.is_empty());
+    assert_eq!(transport.requests().len(), 2);
+}
+
+/// A `/book` response for token `111` with `(price, size)` levels, listed in
+/// the order given.
+fn book_json(bids: &[(&str, &str)], asks: &[(&str, &str)]) -> serde_json::Value {
+    let levels = |levels: &[(&str, &str)]| {
+        levels
+            .iter()
+            .map(|(price, size)| serde_json::json!({"price": price, "size": size}))
+            .collect::<Vec<_>>()
+    };
+    serde_json::json!({
+        "market": "0xcond",
+        "asset_id": "111",
+        "hash": "",
+        "timestamp": "0",
+        "bids": levels(bids),
+        "asks": levels(asks)
+    })
+}
+
+/// One page of `/markets` or `/simplified-markets`.
+fn markets_page(next_cursor: &str, data: Vec<serde_json::Value>) -> serde_json::Value {
+    serde_json::json!({"limit": data.len(), "count": data.len(), "next_cursor": next_cursor, "data": data})
+}
+
+fn trade_json(id: &str) -> serde_json::Value {
+    serde_json::json!({
+        "id": id,
+        "taker_order_id": "0xtaker",
+        "market": "0xcond",
+        "asset_id": "111",
+        "side": "BUY",
+        "size": "10",
+        "fee_rate_bps": "0",
+        "price": "0.5",
+        "status": "MATCHED",
+        "match_time": "1700000000",
+        "last_update": "1700000000",
+        "outcome": "Yes",
+        "bucket_index": 0,
+        "owner": "owner",
+        "maker_address": "0x0000000000000000000000000000000000000001",
+        "maker_orders": [],
+        "transaction_hash": "0xhash",
+        "trader_side": "TAKER"
+    })
+}
+
+#[tokio::test]
+async fn test_export_trades_writes_ndjson() {
+    let transport = MockTransport::default()
+        .reply(
+            "/data/trades",
+            serde_json::json!({"next_cursor": "MTAw", "data": [trade_json("a"), trade_json("b")]}),
+        )
+        .reply(
+            "/data/trades",
+            serde_json::json!({"next_cursor": "LTE=", "data": [trade_json("c")]}),
+        );
+    let client = ClobClient::with_l2_headers("https://clob.example", TEST_KEY, 137, test_creds())
+        .with_transport(transport.clone());
+
+    let mut buf = Vec::new();
+    let count = client.export_trades(None, &mut buf).await.unwrap();
+
+    assert_eq!(count, 3);
+    assert_eq!(transport.requests().len(), 2);
+    let out = String::from_utf8(buf).unwrap();
+    let ids: Vec<_> = out
+        .lines()
+        .map(|l| serde_json::from_str::<Trade>(l).unwrap().id)
+        .collect();
+    assert_eq!(ids, ["a", "b", "c"]);
+}
+
+#[tokio::test]
+async fn test_get_orders_stops_on_missing_cursor() {
+    let transport = MockTransport::default()
+        .reply(
+            "/data/orders",
+            serde_json::json!({"next_cursor": null, "data": [open_order_json("a")]}),
+        )
+        .reply(
+            "/data/orders",
+            serde_json::json!({"next_cursor": "LTE=", "data": [open_order_json("b")]}),
+        );
+    let client = ClobClient::with_l2_headers("https://clob.example", TEST_KEY, 137, test_creds())
+        .with_transport(transport.clone());
+
+    let orders = client.get_orders(None, None).await.unwrap();
+
+    assert_eq!(orders.len(), 1);
+    assert_eq!(transport.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_complementary_token() {
+    let book = book_json(&[], &[]);
+    let transport = MockTransport::default()
+        .reply("/book", book.clone())
+        .reply("/markets/0xcond", crate::data::tests::market_json())
+        .reply("/book", book)
+        .reply("/markets/0xcond", crate::data::tests::market_json());
+    let client = ClobClient::new("https://clob.example").with_transport(transport);
+
+    assert_eq!(
+        client.complementary_token("111").await.unwrap().as_deref(),
+        Some("222")
+    );
+    assert_eq!(client.complementary_token("999").await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_executable_price_uses_side_of_book() {
+    let book = book_json(&[("0.4", "5")], &[("0.7", "5"), ("0.6", "5")]);
+    let transport = MockTransport::default()
+        .reply("/book", book.clone())
+        .reply("/book", book);
+    let client = ClobClient::new("https://clob.example").with_transport(transport);
+
+    assert_eq!(
+        client
+            .executable_price("111", Side::BUY, Decimal::from(8))
+            .await
+            .unwrap(),
+        Some(Decimal::new(7, 1))
+    );
+    assert_eq!(
+        client
+            .executable_price("111", Side::SELL, Decimal::from(8))
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn test_get_top_of_book() {
+    let book = book_json(
+        &[("0.38", "50"), ("0.4", "5")],
+        &[("0.6", "7"), ("0.55", "3")],
+    );
+    let mut one_sided = book.clone();
+    one_sided["asks"] = serde_json::json!([]);
+    let transport = MockTransport::default()
+        .reply("/book", book)
+        .reply("/book", one_sided);
+    let client = ClobClient::new("https://clob.example").with_transport(transport);
+
+    let top = client.get_top_of_book("111").await.unwrap();
+    assert_eq!(
+        top,
+        TopOfBook {
+            best_bid: Some(OrderSummary {
+                price: Decimal::new(4, 1),
+                size: Decimal::from(5)
+            }),
+            best_ask: Some(OrderSummary {
+                price: Decimal::new(55, 2),
+                size: Decimal::from(3)
+            }),
+        }
+    );
+
+    let top = client.get_top_of_book("111").await.unwrap();
+    assert!(top.best_bid.is_some());
+    assert_eq!(top.best_ask, None);
+}
+
+#[tokio::test]
+async fn test_vwap_uses_side_of_book() {
+    let book = book_json(&[("0.2", "10"), ("0.4", "10")], &[("0.6", "5")]);
+    let transport = MockTransport::default()
+        .reply("/book", book.clone())
+        .reply("/book", book);
+    let client = ClobClient::new("https://clob.example").with_transport(transport);
+
+    // 4 USDC from 10 @ 0.4, then 1 USDC from 5 @ 0.2.
+    assert_eq!(
+        client
+            .vwap("111", Side::SELL, Decimal::from(5))
+            .await
+            .unwrap(),
+        Some(Decimal::from(5) / Decimal::from(15))
+    );
+    assert_eq!(
+        client
+            .vwap("111", Side::BUY, Decimal::from(5))
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn test_max_response_bytes() {
+    let markets = serde_json::json!({
+        "limit": 1,
+        "count": 0,
+        "next_cursor": "LTE=",
+        "data": []
+    });
+    let transport = MockTransport::default()
+        .reply_with_status("/markets", 200, &"x".repeat(1024))
+        .reply("/markets", markets);
+    let client = ClobClient::new("https://clob.example")
+        .with_http_config(HttpConfig::default().with_max_response_bytes(Some(256)))
+        .with_transport(transport);
+
+    let err = client.get_markets(None).await.unwrap_err();
+    assert!(
+        err.to_string().contains("exceeds limit of 256 bytes"),
+        "{err}"
+    );
+
+    let resp = client.get_markets(None).await.unwrap();
+    assert!(resp.data.is_empty());
+}
+
+#[tokio::test]
+async fn test_base_path_prefixes_endpoints() {
+    let transport = MockTransport::default()
+        .reply("/clob/v2/markets/0xcond", crate::data::tests::market_json())
+        .reply(
+            "/clob/v2/data/orders",
+            serde_json::json!({"next_cursor": "LTE=", "data": []}),
+        );
+    let client = ClobClient::with_l2_headers("https://gw.example", TEST_KEY, 137, test_creds())
+        .with_base_path("/clob/v2/")
+        .with_transport(transport.clone());
+
+    client.get_market("0xcond").await.unwrap();
+    client.get_orders(None, None).await.unwrap();
+
+    let requests = transport.requests();
+    assert_eq!(requests[0], "https://gw.example/clob/v2/markets/0xcond");
+    assert!(requests[1].starts_with("https://gw.example/clob/v2/data/orders?"));
+}
+
+#[tokio::test]
+async fn test_concurrent_create_order_fetches_tick_size_once() {
+    let transport = MockTransport::default()
+        .reply("/tick-size", serde_json::json!({"minimum_tick_size": 0.01}))
+        .reply("/neg-risk", serde_json::json!({"neg_risk": false}));
+    let client = ClobClient::with_l1_headers("https://clob.example", TEST_KEY, 137)
+        .with_transport(transport.clone());
+    let args = OrderArgs::new("1234", Decimal::new(5, 1), Decimal::TEN, Side::BUY);
+
+    let (a, b) = tokio::join!(
+        client.create_order(
+            &args,
+            None,
+            Some(ExtraOrderArgs::with_fee_rate_bps(0)),
+            None
+        ),
+        client.create_order(
+            &args,
+            None,
+            Some(ExtraOrderArgs::with_fee_rate_bps(0)),
+            None
+        ),
+    );
+    a.unwrap();
+    b.unwrap();
+
+    let requests = transport.requests();
+    let count = |path: &str| requests.iter().filter(|r| r.contains(path)).count();
+    assert_eq!(count("/tick-size"), 1);
+    assert_eq!(count("/neg-risk"), 1);
+}
+
+#[tokio::test]
+async fn test_stream_market_trades_yields_new_events() {
+    use futures_util::StreamExt;
+
+    let event = |id: &str| serde_json::json!({"id": id, "transaction_hash": "0xtx", "side": "BUY"});
+    let transport = MockTransport::default()
+        .reply(
+            "/live-activity/events/0xcond",
+            serde_json::json!([event("1")]),
+        )
+        .reply(
+            "/live-activity/events/0xcond",
+            serde_json::json!([event("2"), event("1")]),
+        );
+    let client = ClobClient::new("https://clob.example").with_transport(transport.clone());
+
+    let stream = client.stream_market_trades("0xcond", Duration::from_millis(1));
+    let mut stream = std::pin::pin!(stream);
+    let first = stream.next().await.unwrap().unwrap();
+    let second = stream.next().await.unwrap().unwrap();
+
+    assert_eq!(first.id.as_deref(), Some("1"));
+    assert_eq!(second.id.as_deref(), Some("2"));
+    assert_eq!(second.data["side"], "BUY");
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn test_html_error_page_is_reported() {
+    let html = "<html><head><title>502 Bad Gateway</title></head><body>cloudflare</body></html>";
+    let transport = MockTransport::default()
+        .reply_with_status("/midpoint", 502, html)
+        .reply_with_status("/midpoint", 200, html);
+    let client = ClobClient::new("https://clob.example").with_transport(transport);
+
+    let err = client.get_midpoint("1").await.unwrap_err().to_string();
+    assert!(
+        err.contains("/midpoint failed with status 502 Bad Gateway"),
+        "{err}"
+    );
+    assert!(err.contains("<title>502 Bad Gateway</title>"), "{err}");
+
+    let err = format!("{:#}", client.get_midpoint("1").await.unwrap_err());
+    assert!(err.contains("Failed to decode"), "{err}");
+    assert!(err.contains("MidpointResponse from 200 OK"), "{err}");
+    assert!(err.contains("cloudflare"), "{err}");
+}
+
+#[tokio::test]
+async fn test_market_cache_ttl() {
+    let transport = MockTransport::default()
+        .reply("/markets/0xcond", crate::data::tests::market_json())
+        .reply("/markets/0xcond", crate::data::tests::market_json());
+    let client = ClobClient::new("https://clob.example")
+        .with_market_cache_ttl(Duration::from_secs(60))
+        .with_transport(transport.clone());
+
+    client.get_market("0xcond").await.unwrap();
+    let cached = client.get_market("0xcond").await.unwrap();
+    assert_eq!(cached.condition_id, "0xcond");
+    assert_eq!(transport.requests().len(), 1);
+
+    client.invalidate_market("0xcond");
+    client.get_market("0xcond").await.unwrap();
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[test]
+fn test_client_capabilities() {
+    let read_only = ClobClient::new("https://clob.example");
+    assert!(!read_only.has_signer());
+    assert!(!read_only.has_api_creds());
+
+    let l1 = ClobClient::with_l1_headers("https://clob.example", TEST_KEY, 137);
+    assert!(l1.has_signer());
+    assert!(!l1.has_api_creds());
+
+    let full = ClobClient::with_l2_headers("https://clob.example", TEST_KEY, 137, test_creds());
+    assert!(full.has_signer());
+    assert!(full.has_api_creds());
+}
+
+#[tokio::test]
+async fn test_get_rewards_history_paginates() {
+    let earning = |market: &str| {
+        serde_json::json!({
+            "date": "2024-11-20",
+            "condition_id": market,
+            "asset_address": "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174",
+            "earnings": "1.5"
+        })
+    };
+    let transport = MockTransport::default()
+        .reply(
+            "/rewards/user",
+            serde_json::json!({"next_cursor": "MTAw", "data": [earning("a")]}),
+        )
+        .reply(
+            "/rewards/user",
+            serde_json::json!({"next_cursor": "LTE=", "data": [earning("b")]}),
+        );
+    let client = ClobClient::with_l2_headers("https://clob.example", TEST_KEY, 137, test_creds())
+        .with_transport(transport.clone());
+    let params = RewardsHistoryParams {
+        date: Some("2024-11-20".into()),
+        ..Default::default()
+    };
+
+    let earnings = client
+        .get_rewards_history(Some(&params), None)
+        .await
+        .unwrap();
+
+    let markets: Vec<_> = earnings.iter().map(|e| e.market.as_str()).collect();
+    assert_eq!(markets, ["a", "b"]);
+    assert!(transport.requests()[0].contains("date=2024-11-20"));
+}
+
+#[tokio::test]
+async fn test_create_order_price_through_book_guard() {
+    let book = book_json(&[("0.4", "10")], &[("0.5", "10")]);
+    let transport = MockTransport::default()
+        .reply("/book", book.clone())
+        .reply("/book", book)
+        .reply("/tick-size", serde_json::json!({"minimum_tick_size": 0.01}));
+    let client = ClobClient::with_l1_headers("https://clob.example", TEST_KEY, 137)
+        .with_transport(transport.clone());
+    let options = CreateOrderOptions {
+        neg_risk: Some(false),
+        max_price_through_book_bps: Some(1000),
+        ..Default::default()
+    };
+
+    let fat_finger = OrderArgs::new("1234", Decimal::new(9, 1), Decimal::TEN, Side::BUY);
+    let err = client
+        .create_order(
+            &fat_finger,
+            None,
+            Some(ExtraOrderArgs::with_fee_rate_bps(0)),
+            Some(&options),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("8000 bps"), "{err}");
+
+    let within = OrderArgs::new("1234", Decimal::new(51, 2), Decimal::TEN, Side::BUY);
+    client
+        .create_order(
+            &within,
+            None,
+            Some(ExtraOrderArgs::with_fee_rate_bps(0)),
+            Some(&options),
+        )
+        .await
+        .unwrap();
+    assert_eq!(transport.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn test_create_order_fetches_min_order_size() {
+    let mut book = book_json(&[], &[]);
+    book["min_order_size"] = "15".into();
+    book["neg_risk"] = false.into();
+    let transport = MockTransport::default()
+        .reply("/tick-size", serde_json::json!({"minimum_tick_size": 0.01}))
+        .reply("/book", book);
+    let client = ClobClient::with_l1_headers("https://clob.example", TEST_KEY, 137)
+        .with_transport(transport.clone());
+    let options = CreateOrderOptions {
+        neg_risk: Some(false),
+        max_size_rounding: Some(Decimal::new(1, 2)),
+        ..Default::default()
+    };
+
+    let args = OrderArgs::new("1234", Decimal::new(5, 1), Decimal::TEN, Side::BUY);
+    let err = client
+        .create_order(
+            &args,
+            None,
+            Some(ExtraOrderArgs::with_fee_rate_bps(0)),
+            Some(&options),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("minimum order size 15"), "{err}");
+    assert!(transport.requests()[1].contains("/book"));
+}
+
+#[tokio::test]
+async fn test_create_order_default_fee_rate() {
+    let transport = MockTransport::default()
+        .reply("/tick-size", serde_json::json!({"minimum_tick_size": 0.01}))
+        .reply("/fee-rate", serde_json::json!({"base_fee": 30}))
+        .reply("/tick-size", serde_json::json!({"minimum_tick_size": 0.01}))
+        .reply_with_status("/fee-rate", 404, "not found");
+    let client = ClobClient::with_l1_headers("https://clob.example", TEST_KEY, 137)
+        .with_transport(transport.clone());
+    let options = CreateOrderOptions {
+        neg_risk: Some(false),
+        ..Default::default()
+    };
+    let order = |token_id| OrderArgs::new(token_id, Decimal::new(5, 1), Decimal::TEN, Side::BUY);
+
+    let first = client
+        .create_order(&order("1234"), None, None, Some(&options))
+        .await
+        .unwrap();
+    let second = client
+        .create_order(&order("1234"), None, None, Some(&options))
+        .await
+        .unwrap();
+    assert_eq!(first.fee_rate_bps, "30");
+    assert_eq!(second.fee_rate_bps, "30");
+
+    // Extras without a fee rate still get the token's.
+    let salted = client
+        .create_order(
+            &order("1234"),
+            None,
+            Some(ExtraOrderArgs::default().with_salt(1)),
+            Some(&options),
+        )
+        .await
+        .unwrap();
+    assert_eq!(salted.fee_rate_bps, "30");
+
+    // A failed lookup fails the order instead of signing a fee of 0.
+    let err = client
+        .create_order(&order("5678"), None, None, Some(&options))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("404"), "{err}");
+
+    let fee_requests = transport
+        .requests()
+        .iter()
+        .filter(|r| r.contains("/fee-rate"))
+        .count();
+    assert_eq!(fee_requests, 2);
+}
+
+#[tokio::test]
+async fn test_create_order_reports_canonical_tick_size() {
+    let transport = MockTransport::default().reply(
+        "/tick-size",
+        serde_json::json!({"minimum_tick_size": "0.010"}),
+    );
+    let client = ClobClient::with_l1_headers("https://clob.example", TEST_KEY, 137)
+        .with_transport(transport);
+    let args = OrderArgs::new("1234", Decimal::new(5, 3), Decimal::TEN, Side::BUY);
+    let options = CreateOrderOptions {
+        neg_risk: Some(false),
+        ..Default::default()
+    };
+
+    let err = client
+        .create_order(
+            &args,
+            None,
+            Some(ExtraOrderArgs::with_fee_rate_bps(0)),
+            Some(&options),
+        )
+        .await
+        .unwrap_err();
+    let out_of_range = err.downcast_ref::<PriceOutOfRange>().unwrap();
+    assert_eq!(out_of_range.min_price.to_string(), "0.01");
+    assert_eq!(out_of_range.max_price.to_string(), "0.99");
+}
+
+#[tokio::test]
+async fn test_skip_price_validation() {
+    let transport = MockTransport::default()
+        .reply("/tick-size", serde_json::json!({"minimum_tick_size": 0.01}));
+    let client = ClobClient::with_l1_headers("https://clob.example", TEST_KEY, 137)
+        .with_transport(transport);
+    let args = OrderArgs::new("1234", Decimal::new(995, 3), Decimal::TEN, Side::BUY);
+    let mut options = CreateOrderOptions {
+        neg_risk: Some(false),
+        ..Default::default()
+    };
+
+    let err = client
+        .create_order(
+            &args,
+            None,
+            Some(ExtraOrderArgs::with_fee_rate_bps(0)),
+            Some(&options),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.downcast_ref::<PriceOutOfRange>().is_some(), "{err}");
+
+    options.skip_price_validation = true;
+    let order = client
+        .create_order(
+            &args,
+            None,
+            Some(ExtraOrderArgs::with_fee_rate_bps(0)),
+            Some(&options),
+        )
+        .await
+        .unwrap();
+    assert_eq!(order.side, "BUY");
+}
+
+#[tokio::test]
+async fn test_unsupported_chain_is_rejected_before_any_request() {
+    let transport = MockTransport::default();
+    let client = ClobClient::with_l1_headers("https://clob.example", TEST_KEY, 1)
+        .with_transport(transport.clone());
+    let args = OrderArgs::new("1234", Decimal::new(5, 1), Decimal::TEN, Side::BUY);
+
+    let err = client
+        .create_order(&args, None, None, None)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Unsupported chain id 1"), "{err}");
+
+    let market_args = MarketOrderArgs {
+        token_id: "1234".into(),
+        amount: Decimal::TEN,
+    };
+    let err = client
+        .create_market_order(&market_args, None, None)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Unsupported chain id 1"), "{err}");
+    assert!(transport.requests().is_empty());
+}
+
+#[tokio::test]
+async fn test_get_order_constraints() {
+    let mut with_constraints = book_json(&[], &[]);
+    with_constraints["min_order_size"] = "5".into();
+    with_constraints["tick_size"] = "0.001".into();
+    with_constraints["neg_risk"] = true.into();
+    let transport = MockTransport::default()
+        .reply("/book", with_constraints)
+        .reply("/book", book_json(&[], &[]))
+        .reply("/tick-size", serde_json::json!({"minimum_tick_size": 0.01}))
+        .reply("/neg-risk", serde_json::json!({"neg_risk": false}))
+        .reply("/markets/0xcond", crate::data::tests::market_json());
+    let client = ClobClient::new("https://clob.example").with_transport(transport.clone());
+
+    let constraints = client.get_order_constraints("111").await.unwrap();
+    assert_eq!(
+        constraints,
+        OrderConstraints {
+            min_tick_size: Decimal::new(1, 3),
+            min_order_size: Decimal::from(5),
+            neg_risk: true,
+        }
+    );
+    assert_eq!(transport.requests().len(), 1);
+    assert_eq!(
+        client.tick_size_cache().get("111"),
+        Some(Decimal::new(1, 3))
+    );
+    assert_eq!(client.neg_risk_cache().get("111"), Some(true));
+
+    let constraints = client.get_order_constraints("222").await.unwrap();
+    assert_eq!(
+        constraints,
+        OrderConstraints {
+            min_tick_size: Decimal::new(1, 2),
+            min_order_size: Decimal::from(5),
+            neg_risk: false,
+        }
+    );
+    assert_eq!(transport.requests().len(), 5);
+}
+
+#[tokio::test]
+async fn test_get_midpoints_ordered() {
+    let transport =
+        MockTransport::default().reply("/midpoints", serde_json::json!({"3": "0.75", "1": "0.5"}));
+    let client = ClobClient::new("https://clob.example").with_transport(transport);
+    let ids = ["1", "2", "3", "1"].map(String::from);
+
+    let midpoints = client.get_midpoints_ordered(&ids).await.unwrap();
+
+    assert_eq!(
+        midpoints,
+        [
+            ("1".to_owned(), Some(Decimal::new(5, 1))),
+            ("2".to_owned(), None),
+            ("3".to_owned(), Some(Decimal::new(75, 2))),
+            ("1".to_owned(), Some(Decimal::new(5, 1))),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_get_spreads_ordered() {
+    let transport =
+        MockTransport::default().reply("/spreads", serde_json::json!({"2": "0.02", "1": "0.1"}));
+    let client = ClobClient::new("https://clob.example").with_transport(transport);
+    let ids = ["2", "9", "1"].map(String::from);
+
+    let spreads = client.get_spreads_ordered(&ids).await.unwrap();
+
+    assert_eq!(
+        spreads,
+        [
+            ("2".to_owned(), Some(Decimal::new(2, 2))),
+            ("9".to_owned(), None),
+            ("1".to_owned(), Some(Decimal::new(1, 1))),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_post_order_raw_sends_body_verbatim() {
+    let transport = MockTransport::default().reply("/order", serde_json::json!({"success": true}));
+    let client = ClobClient::with_l2_headers("https://clob.example", TEST_KEY, 137, test_creds())
+        .with_transport(transport.clone());
+    // Deliberately not in the client's own formatting.
+    let body = r#"{"order": {"salt": 1},  "owner": "key", "orderType": "GTC"}"#;
+
+    client.post_order_raw(body.to_owned()).await.unwrap();
+
+    let sent = &transport.recorded()[0];
+    assert_eq!(sent.body.as_deref(), Some(body));
+    assert_eq!(sent.headers["content-type"], "application/json");
+    let timestamp: u64 = sent.headers["poly_timestamp"]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    let expected = crate::utils::build_hmac_signature_from_str(
+        &test_creds().secret,
+        timestamp,
+        "POST",
+        "/order",
+        Some(body),
+    )
+    .unwrap();
+    assert_eq!(sent.headers["poly_signature"], expected.as_str());
+}
+
+#[tokio::test]
+async fn test_l2_requests_refused_on_excessive_clock_skew() {
+    let server_time = crate::utils::get_current_unix_time_secs() + 120;
+    let transport = MockTransport::default()
+        .reply("/time", serde_json::json!(server_time))
+        .reply(
+            "/data/orders",
+            serde_json::json!({"next_cursor": "LTE=", "data": []}),
+        );
+    let client = ClobClient::with_l2_headers("https://clob.example", TEST_KEY, 137, test_creds())
+        .with_max_clock_skew(Some(Duration::from_secs(30)))
+        .with_transport(transport.clone());
+
+    let skew = client.clock_skew().await.unwrap();
+    assert!((119..=121).contains(&skew), "{skew}");
+    let err = client.get_orders(None, None).await.unwrap_err();
+    assert!(
+        err.to_string().contains("behind the server"),
+        "unexpected error: {err}"
+    );
+    assert_eq!(transport.requests().len(), 1);
+
+    let client = client.with_max_clock_skew(None);
+    assert!(client.get_orders(None, None).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_cancel_stale_orders() {
+    let now = crate::utils::get_current_unix_time_secs();
+    let order = |id: &str, created_at: u64| {
+        let mut json = open_order_json(id);
+        json["created_at"] = serde_json::json!(created_at);
+        json
+    };
+    let transport = MockTransport::default()
+        .reply(
+            "/data/orders",
+            serde_json::json!({
+                "next_cursor": "LTE=",
+                "data": [order("old", now - 600), order("new", now - 5), order("older", now - 3600)]
+            }),
+        )
+        .reply(
+            "/orders",
+            serde_json::json!({"canceled": ["old", "older"], "not_canceled": {}}),
+        );
+    let client = ClobClient::with_l2_headers("https://clob.example", TEST_KEY, 137, test_creds())
+        .with_transport(transport.clone());
+
+    let resp = client.cancel_stale_orders(60).await.unwrap();
+
+    assert_eq!(resp.canceled, ["old", "older"]);
+    let sent = &transport.recorded()[1];
+    assert!(sent.url.ends_with("/orders"));
+    assert_eq!(sent.body.as_deref(), Some(r#"["old", "older"]"#));
+}
+
+#[tokio::test]
+async fn test_get_prices_parses_string_prices() {
+    let transport = MockTransport::default().reply(
+        "/prices",
+        serde_json::json!({
+            "71321045679252212594626385532706912750332728571942532289631379312455583992563": {
+                "BUY": "0.52",
+                "SELL": "0.53"
+            },
+            "222": {"BUY": "0.001"}
+        }),
+    );
+    let client = ClobClient::new("https://clob.example").with_transport(transport);
+    let params = [BookParams {
+        token_id: "222".to_owned(),
+        side: Side::BUY,
+    }];
+
+    let prices = client.get_prices(&params).await.unwrap();
+
+    assert_eq!(prices.len(), 2);
+    let long =
+        &prices["71321045679252212594626385532706912750332728571942532289631379312455583992563"];
+    assert_eq!(long[&Side::BUY], Decimal::new(52, 2));
+    assert_eq!(long[&Side::SELL], Decimal::new(53, 2));
+    assert_eq!(prices["222"][&Side::BUY], Decimal::new(1, 3));
+    assert!(!prices["222"].contains_key(&Side::SELL));
+}
+
+#[tokio::test]
+async fn test_authed_requests_to_unwrapped_endpoints() {
+    let transport = MockTransport::default()
+        .reply("/some/new-endpoint", serde_json::json!({"ok": true}))
+        .reply("/some/other-endpoint", serde_json::json!({"ok": true}));
+    let client = ClobClient::with_l2_headers("https://clob.example", TEST_KEY, 137, test_creds())
+        .with_transport(transport.clone());
+
+    let resp = client
+        .authed_get("/some/new-endpoint", &[("market", "0xcond")])
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let body = serde_json::json!({"a": 1});
+    client
+        .authed_request(Method::POST, "/some/other-endpoint", Some(&body))
+        .await
+        .unwrap();
+
+    let sent = transport.recorded();
+    assert!(sent[0].url.ends_with("/some/new-endpoint?market=0xcond"));
+    assert_eq!(sent[1].body.as_deref(), Some(r#"{"a": 1}"#));
+    for (request, method, path, body) in [
+        (&sent[0], "GET", "/some/new-endpoint", None),
+        (
+            &sent[1],
+            "POST",
+            "/some/other-endpoint",
+            Some(r#"{"a": 1}"#),
+        ),
+    ] {
+        let headers = &request.headers;
+        assert_eq!(headers["poly_api_key"], test_creds().api_key.as_str());
+        assert_eq!(headers["poly_passphrase"], test_creds().passphrase.as_str());
+        let timestamp: u64 = headers["poly_timestamp"].to_str().unwrap().parse().unwrap();
+        let expected = crate::utils::build_hmac_signature_from_str(
+            &test_creds().secret,
+            timestamp,
+            method,
+            path,
+            body,
+        )
+        .unwrap();
+        assert_eq!(headers["poly_signature"], expected.as_str());
+    }
+}
+
+#[tokio::test]
+async fn test_market_order_rejects_stale_book() {
+    let now_ms = crate::utils::get_current_unix_time_millis();
+    let book = |timestamp: u64| {
+        let mut book = book_json(&[("0.4", "100")], &[("0.5", "100")]);
+        book["timestamp"] = timestamp.to_string().into();
+        book
+    };
+    let tick_size = serde_json::json!({"minimum_tick_size": 0.01});
+    let transport = MockTransport::default()
+        .reply("/tick-size", tick_size.clone())
+        .reply("/book", book(now_ms - 60_000))
+        .reply("/tick-size", tick_size)
+        .reply("/book", book(now_ms));
+    let client = ClobClient::with_l1_headers("https://clob.example", TEST_KEY, 137)
+        .with_transport(transport);
+    let args = MarketOrderArgs {
+        token_id: "1234".into(),
+        amount: Decimal::TEN,
+    };
+    let options = CreateOrderOptions {
+        tick_size: Some(Decimal::new(1, 2)),
+        neg_risk: Some(false),
+        max_book_age_ms: Some(5_000),
+        ..Default::default()
+    };
+
+    let err = client
+        .create_market_order(
+            &args,
+            Some(ExtraOrderArgs::with_fee_rate_bps(0)),
+            Some(&options),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Order book is"), "{err}");
+
+    let order = client
+        .create_market_order(
+            &args,
+            Some(ExtraOrderArgs::with_fee_rate_bps(0)),
+            Some(&options),
+        )
+        .await
+        .unwrap();
+    assert_eq!(order.side, "BUY");
+}
+
+#[tokio::test]
+async fn test_validate_api_creds() {
+    let transport = MockTransport::default()
+        .reply(
+            "/auth/api-keys",
+            serde_json::json!({"apiKeys": [test_creds().api_key]}),
+        )
+        .reply_with_status(
+            "/auth/api-keys",
+            401,
+            r#"{"error":"Unauthorized/Invalid api key"}"#,
+        );
+    let client = ClobClient::with_l2_headers("https://clob.example", TEST_KEY, 137, test_creds())
+        .with_transport(transport);
+
+    client.validate_api_creds().await.unwrap();
+    let err = client.validate_api_creds().await.unwrap_err();
+    assert!(
+        err.to_string().contains("was rejected with status 401"),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn test_arbitrage_gap() {
+    let transport = MockTransport::default()
+        .reply("/midpoints", serde_json::json!({"1": "0.52", "2": "0.45"}))
+        .reply("/midpoints", serde_json::json!({"1": "0.6", "2": "0.45"}))
+        .reply("/midpoints", serde_json::json!({"1": "0.6"}))
+        .reply("/midpoints", serde_json::json!({"1": "0.6"}));
+    let client = ClobClient::new("https://clob.example").with_transport(transport);
+
+    assert_eq!(
+        client.arbitrage_gap("1", "2").await.unwrap(),
+        Decimal::new(-3, 2)
+    );
+    assert_eq!(
+        client.arbitrage_gap("1", "2").await.unwrap(),
+        Decimal::new(5, 2)
+    );
+    let err = client.arbitrage_gap("1", "2").await.unwrap_err();
+    assert!(err.to_string().contains("No midpoint for token 2"));
+    // The same token twice counts its midpoint twice.
+    assert_eq!(
+        client.arbitrage_gap("1", "1").await.unwrap(),
+        Decimal::new(2, 1)
+    );
+}
+
+#[derive(Clone, Default)]
+struct SlowTransport {
+    in_flight: Arc<std::sync::atomic::AtomicUsize>,
+    max_in_flight: Arc<std::sync::atomic::AtomicUsize>,
+}
+
+impl Transport for SlowTransport {
+    fn send(&self, request: reqwest::Request) -> TransportFuture<'_> {
+        use std::sync::atomic::Ordering::SeqCst;
+
+        Box::pin(async move {
+            let now = self.in_flight.fetch_add(1, SeqCst) + 1;
+            self.max_in_flight.fetch_max(now, SeqCst);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            self.in_flight.fetch_sub(1, SeqCst);
+            let body = match request.url().path() {
+                "/spread" => r#"{"spread": "0.01"}"#,
+                _ => r#"{"mid": "0.5"}"#,
+            };
+            let resp = http::Response::builder()
+                .url(request.url().clone())
+                .body(body)?;
+            Ok(reqwest::Response::from(resp))
+        })
+    }
+}
+
+/// Answers at once but streams the body slowly, counting bodies being read.
+#[derive(Clone, Default)]
+struct SlowBodyTransport {
+    reading: Arc<std::sync::atomic::AtomicUsize>,
+    max_reading: Arc<std::sync::atomic::AtomicUsize>,
+}
+
+impl Transport for SlowBodyTransport {
+    fn send(&self, request: reqwest::Request) -> TransportFuture<'_> {
+        use std::sync::atomic::Ordering::SeqCst;
+
+        let (reading, max_reading) = (self.reading.clone(), self.max_reading.clone());
+        let body = futures_util::stream::once(async move {
+            let now = reading.fetch_add(1, SeqCst) + 1;
+            max_reading.fetch_max(now, SeqCst);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            reading.fetch_sub(1, SeqCst);
+            Ok::<_, std::io::Error>(r#"{"mid": "0.5"}"#)
+        });
+        Box::pin(async move {
+            let resp = http::Response::builder()
+                .url(request.url().clone())
+                .body(reqwest::Body::wrap_stream(body))?;
+            Ok(reqwest::Response::from(resp))
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_max_concurrent_requests_covers_body() {
+    let transport = SlowBodyTransport::default();
+    let client = ClobClient::new("https://clob.example")
+        .with_max_concurrent_requests(1)
+        .with_transport(transport.clone());
+
+    let midpoints = (0..5).map(|_| client.get_midpoint("1"));
+    let midpoints = futures_util::future::join_all(midpoints).await;
+
+    assert!(midpoints.iter().all(|r| r.is_ok()));
+    assert_eq!(
+        transport
+            .max_reading
+            .load(std::sync::atomic::Ordering::SeqCst),
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_shutdown_rejects_new_requests_and_drains() {
+    let transport = SlowTransport::default();
+    let client =
+        Arc::new(ClobClient::new("https://clob.example").with_transport(transport.clone()));
+
+    let pending = tokio::spawn({
+        let client = client.clone();
+        async move { client.get_midpoint("1").await }
+    });
+    while transport
+        .in_flight
+        .load(std::sync::atomic::Ordering::SeqCst)
+        == 0
+    {
+        tokio::task::yield_now().await;
+    }
+
+    client.shutdown();
+    assert!(client.is_shut_down());
+    let err = client.get_midpoint("2").await.unwrap_err();
+    assert!(err.to_string().contains("Client is shut down"), "{err}");
+
+    client.drain().await;
+    assert_eq!(
+        transport
+            .in_flight
+            .load(std::sync::atomic::Ordering::SeqCst),
+        0
+    );
+    assert_eq!(
+        pending.await.unwrap().unwrap().mid,
+        Some(Decimal::new(5, 1))
+    );
+}
+
+#[tokio::test]
+async fn test_max_concurrent_requests_is_shared() {
+    let transport = SlowTransport::default();
+    let client = ClobClient::new("https://clob.example")
+        .with_max_concurrent_requests(3)
+        .with_transport(transport.clone());
+
+    let midpoints = (0..10).map(|_| client.get_midpoint("1"));
+    let spreads = (0..10).map(|_| client.get_spread("1"));
+    let (midpoints, spreads) = tokio::join!(
+        futures_util::future::join_all(midpoints),
+        futures_util::future::join_all(spreads)
+    );
+
+    assert!(midpoints.iter().all(|r| r.is_ok()));
+    assert!(spreads.iter().all(|r| r.is_ok()));
+    assert_eq!(
+        transport
+            .max_in_flight
+            .load(std::sync::atomic::Ordering::SeqCst),
+        3
+    );
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn test_get_markets_starting_between() {
+    use chrono::TimeZone;
+
+    let market = |condition_id: &str, start: &str| {
+        let mut json = crate::data::tests::market_json();
+        json["condition_id"] = condition_id.into();
+        json["game_start_time"] = start.into();
+        json
+    };
+    let transport = MockTransport::default()
+        .reply(
+            "/markets",
+            markets_page(
+                "MTAw",
+                vec![
+                    market("a", "2024-11-10 01:00:00+00"),
+                    market("b", "2024-11-11 01:00:00+00"),
+                ],
+            ),
+        )
+        .reply(
+            "/markets",
+            markets_page("LTE=", vec![market("c", "2024-11-10T01:15:00Z")]),
+        );
+    let client = ClobClient::new("https://clob.example").with_transport(transport.clone());
+    let from = Utc.with_ymd_and_hms(2024, 11, 10, 0, 30, 0).unwrap();
+
+    let markets = client
+        .get_markets_starting_between(from, from + chrono::Duration::hours(1))
+        .await
+        .unwrap();
+
+    let ids: Vec<_> = markets.iter().map(|m| m.condition_id.as_str()).collect();
+    assert_eq!(ids, ["a", "c"]);
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[test]
+fn test_is_cursor_end() {
+    assert!(is_cursor_end(None));
+    assert!(is_cursor_end(Some("")));
+    assert!(is_cursor_end(Some("LTE=")));
+    assert!(!is_cursor_end(Some("MA==")));
+    assert!(!is_cursor_end(Some("MTAw")));
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn test_markets_pagination_end_markers() {
+    for end in [serde_json::Value::Null, "".into(), "LTE=".into()] {
+        let transport = MockTransport::default()
+            .reply(
+                "/markets",
+                serde_json::json!({"limit": 1, "count": 1, "next_cursor": "MTAw", "data": []}),
+            )
+            .reply(
+                "/markets",
+                serde_json::json!({"limit": 1, "count": 1, "next_cursor": end, "data": []}),
+            );
+        let client = ClobClient::new("https://clob.example").with_transport(transport.clone());
+
+        let now = chrono::Utc::now();
+        client.get_markets_starting_between(now, now).await.unwrap();
+        assert_eq!(transport.requests().len(), 2, "{end:?}");
+    }
+}
+
+#[tokio::test]
+async fn test_set_funder_applies_to_later_orders() {
+    let transport = MockTransport::default()
+        .reply("/tick-size", serde_json::json!({"minimum_tick_size": 0.01}))
+        .reply("/tick-size", serde_json::json!({"minimum_tick_size": 0.01}));
+    let mut client = ClobClient::with_l1_headers("https://clob.example", TEST_KEY, 137)
+        .with_transport(transport);
+    let args = OrderArgs::new("1234", Decimal::new(5, 1), Decimal::TEN, Side::BUY);
+    let options = CreateOrderOptions {
+        neg_risk: Some(false),
+        ..Default::default()
+    };
+    let signer = client.get_address().unwrap();
+    assert_eq!(
+        client.funder().map(|f| f.to_string().to_lowercase()),
+        Some(signer.clone())
+    );
+
+    let before = client
+        .create_order(
+            &args,
+            None,
+            Some(ExtraOrderArgs::with_fee_rate_bps(0)),
+            Some(&options),
+        )
+        .await
+        .unwrap();
+    let proxy: Address = "0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E"
+        .parse()
+        .unwrap();
+    client.set_funder(proxy);
+    client.set_signature_type(SigType::BrowserWalletProxy);
+    let after = client
+        .create_order(
+            &args,
+            None,
+            Some(ExtraOrderArgs::with_fee_rate_bps(0)),
+            Some(&options),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(before.maker.to_lowercase(), signer);
+    assert_eq!(before.signature_type, 0);
+    assert_eq!(after.maker, proxy.to_checksum(None));
+    assert_eq!(after.signer.to_lowercase(), signer);
+    assert_eq!(after.signature_type, 2);
+    assert_eq!(client.funder(), Some(proxy));
+}
+
+#[tokio::test]
+async fn test_get_market_prices() {
+    let transport = MockTransport::default()
+        .reply("/markets/0xcond", crate::data::tests::market_json())
+        .reply(
+            "/midpoints",
+            serde_json::json!({"111": "0.55", "222": "0.47"}),
+        )
+        .reply("/markets/0xcond", crate::data::tests::market_json())
+        .reply("/midpoints", serde_json::json!({"222": "0.47"}));
+    let client = ClobClient::new("https://clob.example").with_transport(transport.clone());
+
+    let prices = client.get_market_prices("0xcond").await.unwrap();
+    assert_eq!(
+        prices,
+        BinaryMarketPrices {
+            yes: Some(Decimal::new(55, 2)),
+            no: Some(Decimal::new(47, 2)),
+            sum: Some(Decimal::new(102, 2)),
+        }
+    );
+
+    let prices = client.get_market_prices("0xcond").await.unwrap();
+    assert_eq!(prices.yes, None);
+    assert_eq!(prices.no, Some(Decimal::new(47, 2)));
+    assert_eq!(prices.sum, None);
+    assert_eq!(
+        transport.recorded()[1].body.as_deref(),
+        Some(r#"[{"token_id":"111"},{"token_id":"222"}]"#)
+    );
+}
+
+#[tokio::test]
+async fn test_get_market_prices_no_listed_first() {
+    let mut market = crate::data::tests::market_json();
+    market["tokens"] = serde_json::json!([
+        {"token_id": "222", "outcome": "No"},
+        {"token_id": "111", "outcome": "Yes"}
+    ]);
+    let transport = MockTransport::default()
+        .reply("/markets/0xcond", market)
+        .reply(
+            "/midpoints",
+            serde_json::json!({"111": "0.55", "222": "0.47"}),
+        );
+    let client = ClobClient::new("https://clob.example").with_transport(transport);
+
+    let prices = client.get_market_prices("0xcond").await.unwrap();
+    assert_eq!(prices.yes, Some(Decimal::new(55, 2)));
+    assert_eq!(prices.no, Some(Decimal::new(47, 2)));
+}
+
+#[tokio::test]
+async fn test_market_order_reports_available_liquidity() {
+    let book = book_json(&[], &[("0.5", "10"), ("0.6", "5")]);
+    let transport = MockTransport::default()
+        .reply("/tick-size", serde_json::json!({"minimum_tick_size": 0.01}))
+        .reply("/book", book);
+    let client = ClobClient::with_l1_headers("https://clob.example", TEST_KEY, 137)
+        .with_transport(transport);
+    let args = MarketOrderArgs {
+        token_id: "1234".into(),
+        amount: Decimal::from(50),
+    };
+    let options = CreateOrderOptions {
+        neg_risk: Some(false),
+        ..Default::default()
+    };
+
+    let err = client
+        .create_market_order(
+            &args,
+            Some(ExtraOrderArgs::with_fee_rate_bps(0)),
+            Some(&options),
+        )
+        .await
+        .unwrap_err();
+
+    let shortfall = err.downcast_ref::<InsufficientLiquidity>().unwrap();
+    assert_eq!(shortfall.requested_amount, Decimal::from(50));
+    assert_eq!(shortfall.available_amount, Decimal::from(8));
+}
+
+#[tokio::test]
+async fn test_join_best() {
+    let mut book = book_json(&[("0.41", "10"), ("0.45", "5")], &[]);
+    book["tick_size"] = "0.01".into();
+    let transport = MockTransport::default()
+        .reply("/book", book.clone())
+        .reply("/book", book);
+    let client = ClobClient::new("https://clob.example").with_transport(transport);
+
+    let args = client
+        .join_best("1234", Side::BUY, Decimal::from(25))
+        .await
+        .unwrap();
+    assert_eq!(args.token_id, "1234");
+    assert_eq!(args.price, Decimal::new(45, 2));
+    assert_eq!(args.size, Decimal::from(25));
+    assert_eq!(args.side, Side::BUY);
+    assert_eq!(
+        client.tick_size_cache().get("1234"),
+        Some(Decimal::new(1, 2))
+    );
+
+    let err = client
+        .join_best("1234", Side::SELL, Decimal::from(25))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("No SELL orders"), "{err}");
+}
+
+#[tokio::test]
+async fn test_join_best_uses_cached_tick_size() {
+    let book = book_json(&[("0.457", "10")], &[]);
+    let transport = MockTransport::default().reply("/book", book);
+    let client = ClobClient::new("https://clob.example").with_transport(transport.clone());
+    client.tick_size_cache().insert("1234", Decimal::new(1, 2));
+
+    let args = client
+        .join_best("1234", Side::BUY, Decimal::from(25))
+        .await
+        .unwrap();
+    assert_eq!(args.price, Decimal::new(45, 2));
+    assert_eq!(transport.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_improve_price() {
+    let mut wide = book_json(
+        &[("0.41", "10"), ("0.45", "5")],
+        &[("0.6", "10"), ("0.55", "5")],
+    );
+    wide["tick_size"] = "0.01".into();
+    let mut pinned = book_json(&[("0.99", "10")], &[]);
+    pinned["tick_size"] = "0.01".into();
+    let transport = MockTransport::default()
+        .reply("/book", wide.clone())
+        .reply("/book", wide)
+        .reply("/book", pinned.clone())
+        .reply("/book", pinned);
+    let client = ClobClient::new("https://clob.example").with_transport(transport);
+
+    let bid = client.improve_price("1234", Side::BUY).await.unwrap();
+    assert_eq!(bid, Some(Decimal::new(46, 2)));
+    let ask = client.improve_price("1234", Side::SELL).await.unwrap();
+    assert_eq!(ask, Some(Decimal::new(54, 2)));
+
+    assert_eq!(client.improve_price("1234", Side::BUY).await.unwrap(), None);
+    let err = client.improve_price("1234", Side::SELL).await.unwrap_err();
+    assert!(err.to_string().contains("No SELL orders"), "{err}");
+}
+
+#[tokio::test]
+async fn test_improve_price_rejects_zero_tick_size() {
+    let mut book = book_json(&[("0.45", "5")], &[("0.55", "5")]);
+    book["tick_size"] = "0".into();
+    let transport = MockTransport::default().reply("/book", book);
+    let client = ClobClient::new("https://clob.example").with_transport(transport);
+
+    let err = client.improve_price("1234", Side::BUY).await.unwrap_err();
+    assert!(
+        err.to_string().contains("Tick size must be positive"),
+        "{err}"
+    );
+    assert_eq!(client.tick_size_cache().get("1234"), None);
+}
+
+#[cfg(feature = "chrono")]
+#[tokio::test]
+async fn test_create_order_before_market_accepts_orders() {
+    let book = book_json(&[], &[]);
+    let opens_at = chrono::Utc::now() + chrono::Duration::hours(2);
+    let mut market = crate::data::tests::market_json();
+    market["accepting_order_timestamp"] = opens_at.to_rfc3339().into();
+    let transport = MockTransport::default()
+        .reply("/book", book)
+        .reply("/markets/0xcond", market)
+        .reply("/tick-size", serde_json::json!({"minimum_tick_size": 0.01}));
+    let client = ClobClient::with_l1_headers("https://clob.example", TEST_KEY, 137)
+        .with_transport(transport);
+    let args = OrderArgs::new("111", Decimal::new(5, 1), Decimal::TEN, Side::BUY);
+    let mut options = CreateOrderOptions {
+        neg_risk: Some(false),
+        check_accepting_order_time: true,
+        ..Default::default()
+    };
+
+    let err = client
+        .create_order(
+            &args,
+            None,
+            Some(ExtraOrderArgs::with_fee_rate_bps(0)),
+            Some(&options),
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Market 0xcond is not accepting orders until"),
+        "{err}"
+    );
+
+    options.check_accepting_order_time = false;
+    client
+        .create_order(
+            &args,
+            None,
+            Some(ExtraOrderArgs::with_fee_rate_bps(0)),
+            Some(&options),
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_stream_reward_markets_skips_unrewarded() {
+    let market = |id: &str, min_size: u32, rates: serde_json::Value| {
+        let mut m = crate::data::tests::market_json();
+        m["condition_id"] = id.into();
+        m["rewards"]["min_size"] = min_size.into();
+        m["rewards"]["rates"] = rates;
+        m
+    };
+    let rate = serde_json::json!([{"asset_address": "0xusdc", "rewards_daily_rate": 10}]);
+    let transport = MockTransport::default()
+        .reply(
+            "/simplified-markets",
+            markets_page(
+                "MTAw",
+                vec![
+                    market("plain", 0, serde_json::Value::Null),
+                    market("sized", 50, serde_json::Value::Null),
+                ],
+            ),
+        )
+        .reply(
+            "/simplified-markets",
+            markets_page(
+                "LTE=",
+                vec![
+                    market("rated", 0, rate),
+                    market("empty", 0, serde_json::json!([])),
+                ],
+            ),
+        );
+    let client = ClobClient::new("https://clob.example").with_transport(transport.clone());
+
+    let ids: Vec<_> = client
+        .stream_reward_markets()
+        .map(|m| m.unwrap().condition_id)
+        .collect()
+        .await;
+
+    assert_eq!(ids, ["sized", "rated"]);
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn test_middleware_runs_around_each_request() {
+    struct Tag;
+    impl Middleware for Tag {
+        fn on_request(&self, request: &mut reqwest::Request) {
+            request
+                .headers_mut()
+                .insert("x-tag", http::HeaderValue::from_static("1"));
+        }
+        fn on_response(&self, response: &mut reqwest::Response) {
+            let teapot = http::Response::builder().status(418).body("").unwrap();
+            *response = reqwest::Response::from(teapot);
+        }
+    }
+
+    let transport = MockTransport::default()
+        .reply("/midpoint", serde_json::json!({"mid": "0.5"}))
+        .reply("/midpoint", serde_json::json!({"mid": "0.5"}))
+        .reply("/", serde_json::json!("OK"));
+    let counter = RequestCounter::default();
+    let client = ClobClient::new("https://clob.example")
+        .with_transport(transport.clone())
+        .with_middleware(counter.clone())
+        .with_middleware(Tag);
+
+    client.get_midpoint("111").await.unwrap_err();
+    client.get_midpoint("222").await.unwrap_err();
+    assert!(!client.get_ok().await);
+
+    assert_eq!(counter.count("/midpoint"), 2);
+    assert_eq!(counter.count("/"), 1);
+    assert_eq!(counter.counts().len(), 2);
+    assert!(transport
+        .recorded()
+        .iter()
+        .all(|r| r.headers.get("x-tag").is_some()));
+}
+
+#[tokio::test]
+async fn test_middleware_skips_requests_after_shutdown() {
+    let transport = MockTransport::default();
+    let counter = RequestCounter::default();
+    let client = ClobClient::new("https://clob.example")
+        .with_transport(transport.clone())
+        .with_middleware(counter.clone());
+
+    client.shutdown();
+    client.get_midpoint("111").await.unwrap_err();
+
+    assert_eq!(counter.count("/midpoint"), 0);
+    assert!(transport.requests().is_empty());
+}
+
+#[tokio::test]
+async fn test_get_market_by_slug_scans_pages() {
+    let market = |condition_id: &str, slug: &str| {
+        let mut json = crate::data::tests::market_json();
+        json["condition_id"] = condition_id.into();
+        json["market_slug"] = slug.into();
+        json
+    };
+    let transport = MockTransport::default()
+        .reply(
+            "/markets",
+            markets_page(
+                "MTAw",
+                vec![market("0xa", "will-a"), market("0xb", "will-b")],
+            ),
+        )
+        .reply(
+            "/markets",
+            markets_page("LTE=", vec![market("0xc", "will-it-rain-tomorrow")]),
+        )
+        .reply(
+            "/markets",
+            markets_page("LTE=", vec![market("0xa", "will-a")]),
+        );
+    let client = ClobClient::new("https://clob.example")
+        .with_transport(transport.clone())
+        .with_market_cache_ttl(Duration::from_secs(60));
+
+    let found = client
+        .get_market_by_slug("will-it-rain-tomorrow")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.condition_id, "0xc");
+    assert_eq!(transport.requests().len(), 2);
+
+    // Resolved markets are cached by condition id.
+    let cached = client.get_market("0xc").await.unwrap();
+    assert_eq!(cached.market_slug, "will-it-rain-tomorrow");
+    assert_eq!(transport.requests().len(), 2);
+
+    let missing = ClobClient::new("https://clob.example").with_transport(transport.clone());
+    assert!(missing.get_market_by_slug("nope").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_snap_orders_fetches_each_tick_size_once() {
+    let transport = MockTransport::default()
+        .reply("/tick-size", serde_json::json!({"minimum_tick_size": 0.01}))
+        .reply(
+            "/tick-size",
+            serde_json::json!({"minimum_tick_size": 0.001}),
+        );
+    let client = ClobClient::new("https://clob.example").with_transport(transport.clone());
+    let price = |p: i64, scale: u32| Decimal::new(p, scale);
+    let mut orders = vec![
+        OrderArgs::new("111", price(537, 3), Decimal::TEN, Side::BUY),
+        OrderArgs::new("222", price(1234, 4), Decimal::TEN, Side::BUY),
+        OrderArgs::new("111", price(531, 3), Decimal::TEN, Side::SELL),
+        OrderArgs::new("111", price(4, 3), Decimal::TEN, Side::BUY),
+        OrderArgs::new("222", price(9996, 4), Decimal::TEN, Side::SELL),
+    ];
+
+    let rejected = client.snap_orders(&mut orders).await.unwrap();
+
+    let prices: Vec<_> = orders.iter().map(|o| o.price).collect();
+    assert_eq!(
+        prices,
+        [
+            price(53, 2),
+            price(123, 3),
+            price(54, 2),
+            price(4, 3),
+            price(9996, 4)
+        ]
+    );
+    let rejected: Vec<_> = rejected.iter().map(|(i, e)| (*i, e.violated)).collect();
+    assert_eq!(rejected, [(3, PriceBound::Min), (4, PriceBound::Max)]);
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn test_snap_orders_rejects_zero_tick_size() {
+    let transport =
+        MockTransport::default().reply("/tick-size", serde_json::json!({"minimum_tick_size": 0}));
+    let client = ClobClient::new("https://clob.example").with_transport(transport);
+    let mut orders = vec![OrderArgs::new(
+        "111",
+        Decimal::new(5, 1),
+        Decimal::TEN,
+        Side::BUY,
+    )];
+
+    let err = client.snap_orders(&mut orders).await.unwrap_err();
+    assert!(
+        err.to_string().contains("Tick size must be positive"),
+        "{err}"
+    );
+    assert_eq!(orders[0].price, Decimal::new(5, 1));
+}
+
+#[tokio::test]
+async fn test_is_exchange_operational() {
+    let transport = MockTransport::default()
+        .reply("/", serde_json::json!("OK"))
+        .reply_with_status("/", 503, "maintenance")
+        .reply_with_status("/", 425, "too early")
+        .reply_with_status("/", 500, "boom");
+    let client = ClobClient::new("https://clob.example").with_transport(transport);
+
+    assert!(client.is_exchange_operational().await.unwrap());
+    assert!(!client.is_exchange_operational().await.unwrap());
+    assert!(!client.is_exchange_operational().await.unwrap());
+    let err = client.is_exchange_operational().await.unwrap_err();
+    assert!(err.to_string().contains("500"));
+}
+
+struct XorCipher;
+
+impl CredsCipher for XorCipher {
+    fn encrypt(&self, plaintext: &[u8]) -> ClientResult<Vec<u8>> {
+        Ok(plaintext.iter().map(|b| b ^ 0x5a).collect())
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> ClientResult<Vec<u8>> {
+        self.encrypt(ciphertext)
+    }
+}
+
+#[tokio::test]
+async fn test_load_or_derive_api_key_caches_to_disk() {
+    let path = std::env::temp_dir().join(format!("polymarket-creds-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let derived = serde_json::to_value(test_creds()).unwrap();
+    let transport = MockTransport::default()
+        // Miss: nothing cached yet.
+        .reply("/auth/api-key", derived.clone())
+        // Hit: the cached key still authenticates.
+        .reply("/auth/api-keys", serde_json::json!({"apiKeys": ["key"]}))
+        // Stale: the cached key was revoked, so it is derived again.
+        .reply_with_status("/auth/api-keys", 401, "Unauthorized")
+        .reply("/auth/api-key", derived);
+    let mut client = ClobClient::with_l1_headers("https://clob.example", TEST_KEY, 137)
+        .with_transport(transport.clone());
+
+    let creds = client
+        .load_or_derive_api_key(None, &path, None)
+        .await
+        .unwrap();
+    assert_eq!(creds.api_key, "key");
+    let cached: ApiCreds = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    assert_eq!(cached.api_key, "key");
+    assert!(transport.requests()[0].ends_with("/auth/api-key"));
+
+    let creds = client
+        .load_or_derive_api_key(None, &path, None)
+        .await
+        .unwrap();
+    assert_eq!(creds.api_key, "key");
+    assert_eq!(transport.requests().len(), 2);
+
+    client
+        .load_or_derive_api_key(None, &path, None)
+        .await
+        .unwrap();
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 4);
+    assert!(requests[3].ends_with("/auth/api-key"));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_load_or_derive_api_key_with_cipher() {
+    let path = std::env::temp_dir().join(format!(
+        "polymarket-creds-cipher-{}.json",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    let transport = MockTransport::default()
+        .reply("/auth/api-key", serde_json::to_value(test_creds()).unwrap())
+        .reply("/auth/api-keys", serde_json::json!({"apiKeys": ["key"]}));
+    let mut client = ClobClient::with_l1_headers("https://clob.example", TEST_KEY, 137)
+        .with_transport(transport.clone());
+
+    client
+        .load_or_derive_api_key(None, &path, Some(&XorCipher))
+        .await
+        .unwrap();
+    let stored = std::fs::read(&path).unwrap();
+    assert!(serde_json::from_slice::<ApiCreds>(&stored).is_err());
+
+    let creds = client
+        .load_or_derive_api_key(None, &path, Some(&XorCipher))
+        .await
+        .unwrap();
+    assert_eq!(creds.passphrase, "pass");
+    assert_eq!(transport.requests().len(), 2);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_api_creds_pool_round_robin() {
+    let creds = |key: &str| ApiCreds {
+        api_key: key.into(),
+        ..test_creds()
+    };
+    let mut transport = MockTransport::default();
+    for _ in 0..4 {
+        transport = transport.reply("/notifications", serde_json::json!([]));
+    }
+    let mut client = ClobClient::with_l1_headers("https://clob.example", TEST_KEY, 137)
+        .with_transport(transport.clone());
+    assert!(client.set_api_creds_pool(Vec::new()).is_err());
+    client
+        .set_api_creds_pool(vec![creds("a"), creds("b"), creds("c")])
+        .unwrap();
+
+    for _ in 0..4 {
+        client.get_notifications().await.unwrap();
+    }
+
+    let keys: Vec<_> = transport
+        .recorded()
+        .iter()
+        .map(|r| r.headers["poly_api_key"].to_str().unwrap().to_owned())
+        .collect();
+    assert_eq!(keys, ["a", "b", "c", "a"]);
+}
+
+#[tokio::test]
+async fn test_api_key_management_uses_primary_creds() {
+    let creds = |key: &str| ApiCreds {
+        api_key: key.into(),
+        ..test_creds()
+    };
+    let transport = MockTransport::default()
+        .reply("/notifications", serde_json::json!([]))
+        .reply("/auth/api-key", serde_json::json!("OK"))
+        .reply("/auth/api-keys", serde_json::json!({"apiKeys": []}))
+        .reply("/notifications", serde_json::json!([]));
+    let mut client = ClobClient::with_l1_headers("https://clob.example", TEST_KEY, 137)
+        .with_transport(transport.clone());
+    client
+        .set_api_creds_pool(vec![creds("a"), creds("b")])
+        .unwrap();
+
+    // Rotation has moved on to "b", but key management stays on "a".
+    client.get_notifications().await.unwrap();
+    client.delete_api_key().await.unwrap();
+    client.validate_api_creds().await.unwrap();
+
+    // Swapping credentials drops the old pool.
+    client.set_api_creds(creds("z")).unwrap();
+    client.get_notifications().await.unwrap();
+
+    let keys: Vec<_> = transport
+        .recorded()
+        .iter()
+        .map(|r| r.headers["poly_api_key"].to_str().unwrap().to_owned())
+        .collect();
+    assert_eq!(keys, ["a", "a", "a", "z"]);
+}
+
+#[tokio::test]
+async fn test_reward_summary() {
+    let order = |id: &str, market: &str| {
+        let mut json = open_order_json(id);
+        json["market"] = market.into();
+        json
+    };
+    let market = |condition_id: &str, rates: serde_json::Value| {
+        let mut json = crate::data::tests::market_json();
+        json["condition_id"] = condition_id.into();
+        json["rewards"]["rates"] = rates;
+        json
+    };
+    let transport = MockTransport::default()
+        .reply(
+            "/data/orders",
+            serde_json::json!({"next_cursor": "MTAw", "data": [order("a", "m1"), order("b", "m2")]}),
+        )
+        .reply(
+            "/data/orders",
+            serde_json::json!({"next_cursor": "LTE=", "data": [order("c", "m1")]}),
+        )
+        // "c" is left out of the scoring response.
+        .reply("/orders-scoring", serde_json::json!({"a": true, "b": false}))
+        .reply(
+            "/markets/m1",
+            market(
+                "m1",
+                serde_json::json!([{"asset_address": "0x2791", "rewards_daily_rate": 25}]),
+            ),
+        )
+        .reply("/markets/m2", market("m2", serde_json::Value::Null));
+    let client = ClobClient::with_l2_headers("https://clob.example", TEST_KEY, 137, test_creds())
+        .with_transport(transport.clone());
+
+    let summary = client.reward_summary().await.unwrap();
+
+    let status = |order_id: &str, market: &str, scoring, daily_rate| RewardStatus {
+        order_id: order_id.into(),
+        market: market.into(),
+        scoring,
+        daily_rate,
+    };
+    assert_eq!(
+        summary,
+        [
+            status("a", "m1", true, Some(Decimal::from(25))),
+            status("b", "m2", false, None),
+            status("c", "m1", false, Some(Decimal::from(25))),
+        ]
+    );
+    // Each market is looked up once.
+    assert_eq!(transport.requests().len(), 5);
+}
+
+#[tokio::test]
+async fn test_get_order_by_hash() {
+    let hash = "0xabababababababababababababababababababababababababababababababab";
+    let transport = MockTransport::default()
+        .reply(&format!("/data/order/{hash}"), open_order_json(hash))
+        .reply(&format!("/data/order/{hash}"), open_order_json("0xother"));
+    let client = ClobClient::with_l2_headers("https://clob.example", TEST_KEY, 137, test_creds())
+        .with_transport(transport.clone());
+
+    // Prefix case, hex case and surrounding whitespace are normalized away.
+    let messy = format!(" 0X{} ", hash[2..].to_ascii_uppercase());
+    let order = client.get_order_by_hash(&messy).await.unwrap();
+    assert_eq!(order.id, hash);
+    assert!(transport.requests()[0].ends_with(&format!("/data/order/{hash}")));
+
+    let err = client.get_order_by_hash(hash).await.unwrap_err();
+    assert!(err.to_string().contains("Order 0xother returned"), "{err}");
+
+    let err = client.get_order_by_hash("0x1234").await.unwrap_err();
+    assert!(err.to_string().contains("Invalid order hash"), "{err}");
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn test_reconcile_partially_filled_order() {
+    let mut order = open_order_json("0xorder");
+    order["original_size"] = "10".into();
+    order["size_matched"] = "6".into();
+    order["associate_trades"] = serde_json::json!(["t1", "t2"]);
+
+    let mut as_taker = trade_json("t1");
+    as_taker["taker_order_id"] = "0xorder".into();
+    as_taker["size"] = "2".into();
+    as_taker["price"] = "0.5".into();
+    let mut as_maker = trade_json("t2");
+    as_maker["price"] = "0.7".into();
+    as_maker["maker_orders"] = serde_json::json!([{
+        "order_id": "0xorder",
+        "maker_address": "0x0000000000000000000000000000000000000001",
+        "owner": "key",
+        "matched_amount": "4",
+        "price": "0.65",
+        "asset_id": "123",
+        "outcome": "Yes"
+    }]);
+
+    let transport = MockTransport::default()
+        .reply("/data/order/0xorder", order)
+        .reply(
+            "/data/trades",
+            serde_json::json!({"next_cursor": "LTE=", "data": [as_taker]}),
+        )
+        .reply(
+            "/data/trades",
+            serde_json::json!({"next_cursor": "LTE=", "data": [as_maker]}),
+        );
+    let client = ClobClient::with_l2_headers("https://clob.example", TEST_KEY, 137, test_creds())
+        .with_transport(transport.clone());
+
+    let report = client.reconcile_order("0xorder").await.unwrap();
+
+    assert_eq!(
+        report,
+        FillReport {
+            order_id: "0xorder".into(),
+            original_size: Decimal::from(10),
+            filled_size: Decimal::from(6),
+            // (2 * 0.5 + 4 * 0.65) / 6
+            avg_fill_price: Some(Decimal::new(6, 1)),
+            remaining: Decimal::from(4),
+        }
+    );
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 3);
+    assert!(requests[1].contains("id=t1"), "{}", requests[1]);
+    assert!(requests[2].contains("id=t2"), "{}", requests[2]);
+    assert!(!requests[1].contains("asset_id"), "{}", requests[1]);
+}
+
+#[tokio::test]
+async fn test_reconcile_scans_asset_without_associate_trades() {
+    let mut order = open_order_json("0xorder");
+    order["original_size"] = "10".into();
+    order["size_matched"] = "2".into();
+
+    let mut fill = trade_json("t1");
+    fill["taker_order_id"] = "0xorder".into();
+    fill["size"] = "2".into();
+    fill["price"] = "0.5".into();
+    let unrelated = trade_json("t2");
+
+    let transport = MockTransport::default()
+        .reply("/data/order/0xorder", order)
+        .reply(
+            "/data/trades",
+            serde_json::json!({"next_cursor": "LTE=", "data": [unrelated, fill]}),
+        );
+    let client = ClobClient::with_l2_headers("https://clob.example", TEST_KEY, 137, test_creds())
+        .with_transport(transport.clone());
+
+    let report = client.reconcile_order("0xorder").await.unwrap();
+
+    assert_eq!(report.filled_size, Decimal::from(2));
+    assert_eq!(report.avg_fill_price, Some(Decimal::new(5, 1)));
+    assert!(transport.requests()[1].contains("asset_id=123"));
+}
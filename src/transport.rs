@@ -0,0 +1,106 @@
+use crate::ClientResult;
+use anyhow::{bail, ensure, Context};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Client, Request, Response, ResponseBuilderExt};
+use serde::de::DeserializeOwned;
+use std::any::type_name;
+use std::future::Future;
+use std::pin::Pin;
+
+pub type TransportFuture<'a> = Pin<Box<dyn Future<Output = ClientResult<Response>> + Send + 'a>>;
+
+/// Sends fully built HTTP requests on behalf of [`crate::ClobClient`].
+///
+/// The client uses its reqwest [`Client`] by default. Providing another
+/// implementation allows scripting responses in tests without a server.
+pub trait Transport: Send + Sync {
+    fn send(&self, request: Request) -> TransportFuture<'_>;
+}
+
+impl Transport for Client {
+    fn send(&self, request: Request) -> TransportFuture<'_> {
+        Box::pin(async move { Ok(self.execute(request).await?) })
+    }
+}
+
+/// Reads the whole body of `resp` into memory, failing as soon as it grows past
+/// `max` bytes when given.
+pub(crate) async fn buffer_response_body(
+    mut resp: Response,
+    max: Option<usize>,
+) -> ClientResult<Response> {
+    if let (Some(len), Some(max)) = (resp.content_length(), max) {
+        ensure!(
+            len <= max as u64,
+            "Response body of {len} bytes exceeds limit of {max} bytes"
+        );
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = resp.chunk().await? {
+        if let Some(max) = max {
+            ensure!(
+                body.len() + chunk.len() <= max,
+                "Response body exceeds limit of {max} bytes"
+            );
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    let mut builder = http::Response::builder()
+        .status(resp.status())
+        .version(resp.version())
+        .url(resp.url().clone());
+    if let Some(headers) = builder.headers_mut() {
+        *headers = resp.headers().clone();
+    }
+    Ok(Response::from(builder.body(body)?))
+}
+
+const BODY_SNIPPET_LEN: usize = 200;
+
+fn body_snippet(body: &[u8]) -> String {
+    let body = String::from_utf8_lossy(body);
+    let body = body.trim();
+    match body.char_indices().nth(BODY_SNIPPET_LEN) {
+        Some((idx, _)) => format!("{}...", &body[..idx]),
+        None => body.to_owned(),
+    }
+}
+
+/// Fails with the status and the start of the body on a non 2xx response.
+pub(crate) async fn check_status(resp: Response) -> ClientResult<Response> {
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(resp);
+    }
+    let url = resp.url().path().to_owned();
+    let body = resp.bytes().await.unwrap_or_default();
+    bail!(
+        "Request to {url} failed with status {status}: {}",
+        body_snippet(&body)
+    )
+}
+
+/// Checks the status, then decodes the body as JSON. Non JSON bodies, like the
+/// HTML error pages served by the CDN, are reported with their content type
+/// and a snippet instead of a bare serde error.
+pub(crate) async fn decode_json<T: DeserializeOwned>(resp: Response) -> ClientResult<T> {
+    let resp = check_status(resp).await?;
+    let status = resp.status();
+    let content_type = resp
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("unknown")
+        .to_owned();
+    let body = resp.bytes().await?;
+
+    serde_json::from_slice(&body).with_context(|| {
+        format!(
+            "Failed to decode {} from {status} response with content type {content_type}: {}",
+            type_name::<T>(),
+            body_snippet(&body)
+        )
+    })
+}
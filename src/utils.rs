@@ -1,5 +1,7 @@
-use anyhow::{Context, Result};
+use alloy_primitives::U256;
+use anyhow::{anyhow, Context, Result};
 use base64::{engine::general_purpose::URL_SAFE, Engine};
+use rust_decimal::Decimal;
 use serde::Serialize;
 use serde_json_fmt::JsonFormat;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -16,6 +18,14 @@ pub fn get_current_unix_time_secs() -> u64 {
         .as_secs()
 }
 
+pub fn get_current_unix_time_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_millis() as u64
+}
+
+#[allow(dead_code)]
 pub fn build_hmac_signature<T>(
     secret: &str,
     timestamp: u64,
@@ -33,6 +43,35 @@ where
     build_hmac_signature_from_str(secret, timestamp, method, req_path, body.as_deref())
 }
 
+/// Normalizes an order hash to the `0x`-prefixed lowercase form used as order id by the CLOB.
+pub fn normalize_order_hash(hash: &str) -> Result<String> {
+    let hash = hash.trim();
+    let hex = hash
+        .strip_prefix("0x")
+        .or_else(|| hash.strip_prefix("0X"))
+        .unwrap_or(hash);
+    if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(anyhow!(
+            "Invalid order hash {hash}, expected 32 hex encoded bytes"
+        ));
+    }
+    Ok(format!("0x{}", hex.to_ascii_lowercase()))
+}
+
+/// Parses a CLOB token id, which is a base 10 encoded uint256.
+pub fn parse_token_id(token_id: &str) -> Result<U256> {
+    if token_id.is_empty() || !token_id.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(anyhow!(
+            "Incorrect tokenId format {token_id:?}, expected a decimal integer"
+        ));
+    }
+    U256::from_str_radix(token_id, 10).context("Incorrect tokenId format, exceeds uint256")
+}
+
+pub fn token_id_to_decimal_string(token_id: U256) -> String {
+    token_id.to_string()
+}
+
 pub fn build_hmac_signature_from_str(
     secret: &str,
     timestamp: u64,
@@ -56,6 +95,9 @@ pub fn build_hmac_signature_from_str(
     Ok(URL_SAFE.encode(&result.into_bytes()[..]))
 }
 
+/// Formats a request body the same way the official clients do before signing
+/// (`", "` and `": "` separators at every nesting level). The exact string
+/// returned here must also be the body that is sent.
 pub fn format_hmac_body<T>(body: &T) -> Result<String>
 where
     T: ?Sized + Serialize,
@@ -66,11 +108,51 @@ where
         .format_to_string(body)?)
 }
 
+/// `price` read as an implied probability. Prices are probabilities already, so
+/// this only rejects values outside `[0, 1]`.
+pub fn implied_probability(price: Decimal) -> Result<Decimal> {
+    if price < Decimal::ZERO || price > Decimal::ONE {
+        return Err(anyhow!(
+            "Price {price} is not a probability between 0 and 1"
+        ));
+    }
+    Ok(price)
+}
+
+/// Fair price of the other outcome of a binary market, `1 - price`.
+pub fn complement_price(price: Decimal) -> Decimal {
+    Decimal::ONE - price
+}
+
+/// Serializes `items` as newline delimited JSON, one compact object per line.
+pub fn to_ndjson<T: Serialize>(items: &[T]) -> Result<String> {
+    let mut out = String::new();
+    for item in items {
+        out.push_str(&serde_json::to_string(item)?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::collections::HashMap;
 
+    #[test]
+    fn test_parse_token_id() {
+        let id = "71321045679252212594626385532706912750332728571942532289631379312455583992563";
+        let parsed = parse_token_id(id).unwrap();
+        assert_eq!(token_id_to_decimal_string(parsed), id);
+        assert_eq!(parse_token_id("0").unwrap(), U256::ZERO);
+
+        for bad in ["", " 1", "12a3", "0x1f", "-1", "1.5"] {
+            assert!(parse_token_id(bad).is_err(), "{bad:?}");
+        }
+        let too_big = format!("{}0", U256::MAX);
+        assert!(parse_token_id(&too_big).is_err());
+    }
+
     #[test]
     fn test_build_hmac_signature() {
         let body = HashMap::from([("hash", "0x123")]);
@@ -85,4 +167,66 @@ mod tests {
 
         assert_eq!(signature, "ZwAdJKvoYRlEKDkNMwd5BuwNNtg93kNaR_oU2HrfVvc=");
     }
+
+    #[test]
+    fn test_normalize_order_hash() {
+        let hash = "0xABCDEF0000000000000000000000000000000000000000000000000000000001";
+        let expected = "0xabcdef0000000000000000000000000000000000000000000000000000000001";
+
+        assert_eq!(normalize_order_hash(hash).unwrap(), expected);
+        assert_eq!(
+            normalize_order_hash(&format!(" {} ", &hash[2..])).unwrap(),
+            expected
+        );
+        assert!(normalize_order_hash("0x1234").is_err());
+        assert!(normalize_order_hash(&hash.replace('A', "z")).is_err());
+    }
+
+    #[test]
+    fn test_format_hmac_body_array() {
+        let body = vec!["0xaaa".to_owned(), "0xbbb".to_owned()];
+        assert_eq!(format_hmac_body(&body).unwrap(), r#"["0xaaa", "0xbbb"]"#);
+
+        let empty: Vec<String> = Vec::new();
+        assert_eq!(format_hmac_body(&empty).unwrap(), "[]");
+    }
+
+    #[test]
+    fn test_format_hmac_body_nested() {
+        #[derive(Serialize)]
+        struct Inner {
+            salt: u64,
+            sides: Vec<&'static str>,
+        }
+
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Outer {
+            order: Inner,
+            order_type: &'static str,
+        }
+
+        let body = vec![Outer {
+            order: Inner {
+                salt: 1,
+                sides: vec!["BUY", "SELL"],
+            },
+            order_type: "GTC",
+        }];
+
+        assert_eq!(
+            format_hmac_body(&body).unwrap(),
+            r#"[{"order": {"salt": 1, "sides": ["BUY", "SELL"]}, "orderType": "GTC"}]"#
+        );
+    }
+
+    #[test]
+    fn test_implied_probability_and_complement() {
+        let price = Decimal::new(35, 2);
+        assert_eq!(implied_probability(price).unwrap(), price);
+        assert_eq!(implied_probability(Decimal::ONE).unwrap(), Decimal::ONE);
+        assert!(implied_probability(Decimal::new(101, 2)).is_err());
+        assert!(implied_probability(Decimal::new(-1, 2)).is_err());
+        assert_eq!(complement_price(price), Decimal::new(65, 2));
+    }
 }
@@ -0,0 +1,392 @@
+use crate::{TickSizeChangeEvent, TokenCache};
+use futures_util::{SinkExt, StreamExt};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::time::{interval_at, sleep, Instant};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, WebSocketStream};
+
+pub const MARKET_WS_URL: &str = "wss://ws-subscriptions-clob.polymarket.com/ws/market";
+
+/// Connection settings for [`WsManager`].
+///
+/// The server drops connections that stay silent, so a `PING` is sent every
+/// `ping_interval`. After a disconnect the manager waits `reconnect_delay`
+/// before dialing again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WsConfig {
+    pub reconnect_delay: Duration,
+    pub ping_interval: Duration,
+}
+
+impl Default for WsConfig {
+    fn default() -> Self {
+        Self {
+            reconnect_delay: Duration::from_secs(1),
+            ping_interval: Duration::from_secs(10),
+        }
+    }
+}
+
+impl WsConfig {
+    pub fn with_reconnect_delay(mut self, delay: Duration) -> Self {
+        self.reconnect_delay = delay;
+        self
+    }
+
+    pub fn with_ping_interval(mut self, interval: Duration) -> Self {
+        self.ping_interval = interval;
+        self
+    }
+}
+
+enum Command {
+    Subscribe(String, UnboundedSender<Value>),
+    Unsubscribe(String),
+}
+
+type Subscribers = HashMap<String, Vec<UnboundedSender<Value>>>;
+type TickSizes = Option<Arc<TokenCache<Decimal>>>;
+
+/// A single market channel connection shared by any number of token subscriptions.
+///
+/// A background task owns the socket, fans incoming events out to the channel of
+/// every token they mention and subscribes the full token set again after a
+/// reconnect. Dropping the manager closes the connection.
+pub struct WsManager {
+    commands: UnboundedSender<Command>,
+}
+
+impl WsManager {
+    /// Starts the background task; must be called from within a tokio runtime.
+    pub fn new(url: &str) -> Self {
+        Self::with_config(url, WsConfig::default())
+    }
+
+    pub fn with_config(url: &str, config: WsConfig) -> Self {
+        Self::spawn(url, config, None)
+    }
+
+    /// Like [`WsManager::with_config`], but also keeps `tick_sizes` in sync with
+    /// `tick_size_change` events, e.g. the cache from
+    /// [`crate::ClobClient::tick_size_cache`]. Changes are applied for every
+    /// token the connection hears about, subscribed to or not.
+    pub fn with_tick_size_cache(
+        url: &str,
+        config: WsConfig,
+        tick_sizes: Arc<TokenCache<Decimal>>,
+    ) -> Self {
+        Self::spawn(url, config, Some(tick_sizes))
+    }
+
+    fn spawn(url: &str, config: WsConfig, tick_sizes: TickSizes) -> Self {
+        let (commands, rx) = mpsc::unbounded_channel();
+        tokio::spawn(run(url.to_owned(), config, tick_sizes, rx));
+        Self { commands }
+    }
+
+    /// Receives every market event mentioning `token_id`. Dropping the receiver
+    /// unsubscribes once no other receiver for the token is left.
+    pub fn subscribe(&self, token_id: &str) -> UnboundedReceiver<Value> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let _ = self
+            .commands
+            .send(Command::Subscribe(token_id.to_owned(), tx));
+        rx
+    }
+
+    pub fn unsubscribe(&self, token_id: &str) {
+        let _ = self
+            .commands
+            .send(Command::Unsubscribe(token_id.to_owned()));
+    }
+}
+
+async fn run(
+    url: String,
+    config: WsConfig,
+    tick_sizes: TickSizes,
+    mut commands: UnboundedReceiver<Command>,
+) {
+    let mut subscribers = Subscribers::new();
+    loop {
+        if let Ok((socket, _)) = connect_async(url.as_str()).await {
+            if !serve(
+                socket,
+                &config,
+                &tick_sizes,
+                &mut subscribers,
+                &mut commands,
+            )
+            .await
+            {
+                return;
+            }
+        }
+
+        // Keep track of (un)subscriptions while waiting to reconnect.
+        let delay = sleep(config.reconnect_delay);
+        tokio::pin!(delay);
+        loop {
+            tokio::select! {
+                _ = &mut delay => break,
+                cmd = commands.recv() => match cmd {
+                    Some(cmd) => {
+                        apply(&mut subscribers, cmd);
+                    }
+                    None => return,
+                },
+            }
+        }
+    }
+}
+
+/// Drives one connection. Returns `false` once the manager is dropped and
+/// `true` when the connection is lost and should be reestablished.
+async fn serve<S>(
+    mut socket: WebSocketStream<S>,
+    config: &WsConfig,
+    tick_sizes: &TickSizes,
+    subscribers: &mut Subscribers,
+    commands: &mut UnboundedReceiver<Command>,
+) -> bool
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    // The server expects the `type: "market"` handshake before any
+    // subscribe/unsubscribe operation on a connection.
+    let mut handshaken = false;
+    if !subscribers.is_empty() {
+        if socket.send(handshake(subscribers)).await.is_err() {
+            return true;
+        }
+        handshaken = true;
+    }
+
+    let mut ping = interval_at(Instant::now() + config.ping_interval, config.ping_interval);
+    loop {
+        let mut outgoing = tokio::select! {
+            cmd = commands.recv() => match cmd {
+                Some(cmd) => apply(subscribers, cmd),
+                None => {
+                    let _ = socket.close(None).await;
+                    return false;
+                }
+            },
+            msg = socket.next() => match msg {
+                Some(Ok(Message::Text(text))) => dispatch(subscribers, tick_sizes.as_deref(), &text)
+                    .into_iter()
+                    .map(|token| operation("unsubscribe", &token))
+                    .collect(),
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return true,
+                Some(Ok(_)) => Vec::new(),
+            },
+            _ = ping.tick() => vec![Message::Text("PING".into())],
+        };
+        if !handshaken && !subscribers.is_empty() {
+            outgoing.retain(|msg| matches!(msg, Message::Text(t) if t == "PING"));
+            outgoing.push(handshake(subscribers));
+            handshaken = true;
+        }
+
+        for msg in outgoing {
+            if socket.send(msg).await.is_err() {
+                return true;
+            }
+        }
+    }
+}
+
+fn handshake(subscribers: &Subscribers) -> Message {
+    let msg = json!({"assets_ids": subscribers.keys().collect::<Vec<_>>(), "type": "market"});
+    Message::Text(msg.to_string())
+}
+
+fn operation(op: &str, token_id: &str) -> Message {
+    Message::Text(json!({"assets_ids": [token_id], "operation": op}).to_string())
+}
+
+/// Updates the subscriber set, returning the messages that sync it with the server.
+fn apply(subscribers: &mut Subscribers, cmd: Command) -> Vec<Message> {
+    match cmd {
+        Command::Subscribe(token, tx) => {
+            let senders = subscribers.entry(token.clone()).or_default();
+            senders.push(tx);
+            match senders.len() {
+                1 => vec![operation("subscribe", &token)],
+                _ => Vec::new(),
+            }
+        }
+        Command::Unsubscribe(token) => match subscribers.remove(&token) {
+            Some(_) => vec![operation("unsubscribe", &token)],
+            None => Vec::new(),
+        },
+    }
+}
+
+fn event_asset_ids(event: &Value) -> Vec<&str> {
+    let mut ids = Vec::new();
+    if let Some(id) = event["asset_id"].as_str() {
+        ids.push(id);
+    }
+    if let Some(changes) = event["price_changes"].as_array() {
+        ids.extend(changes.iter().filter_map(|c| c["asset_id"].as_str()));
+    }
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+/// Stores the new tick size of a `tick_size_change` event. A malformed one
+/// drops the cached value instead, so the next lookup refetches it.
+fn update_tick_size(tick_sizes: &TokenCache<Decimal>, event: &Value) {
+    if event["event_type"] != "tick_size_change" {
+        return;
+    }
+    match TickSizeChangeEvent::deserialize(event) {
+        Ok(change) => tick_sizes.insert(&change.asset_id, change.new_tick_size),
+        Err(_) => {
+            if let Some(id) = event["asset_id"].as_str() {
+                tick_sizes.remove(id);
+            }
+        }
+    }
+}
+
+/// Fans a text frame out to subscribers, returning tokens whose receivers are
+/// all gone. Frames that are not JSON, like `PONG`, are ignored.
+fn dispatch(
+    subscribers: &mut Subscribers,
+    tick_sizes: Option<&TokenCache<Decimal>>,
+    text: &str,
+) -> Vec<String> {
+    let events = match serde_json::from_str::<Value>(text) {
+        Ok(Value::Array(events)) => events,
+        Ok(event) => vec![event],
+        Err(_) => return Vec::new(),
+    };
+
+    let mut abandoned = Vec::new();
+    for event in &events {
+        if let Some(tick_sizes) = tick_sizes {
+            update_tick_size(tick_sizes, event);
+        }
+        for id in event_asset_ids(event) {
+            let Some(senders) = subscribers.get_mut(id) else {
+                continue;
+            };
+            senders.retain(|tx| tx.send(event.clone()).is_ok());
+            if senders.is_empty() {
+                subscribers.remove(id);
+                abandoned.push(id.to_owned());
+            }
+        }
+    }
+    abandoned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+    use tokio::time::timeout;
+    use tokio_tungstenite::accept_async;
+
+    const WAIT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn test_dispatch_routes_by_asset_id() {
+        let mut subscribers = Subscribers::new();
+        let (tx1, mut rx1) = mpsc::unbounded_channel();
+        let (tx2, rx2) = mpsc::unbounded_channel();
+        subscribers.insert("1".into(), vec![tx1]);
+        subscribers.insert("2".into(), vec![tx2]);
+        drop(rx2);
+
+        let frame = json!([
+            {"event_type": "book", "asset_id": "1"},
+            {"event_type": "price_change", "price_changes": [{"asset_id": "1"}, {"asset_id": "2"}]}
+        ]);
+        let abandoned = dispatch(&mut subscribers, None, &frame.to_string());
+
+        assert_eq!(abandoned, ["2"]);
+        assert_eq!(rx1.try_recv().unwrap()["event_type"], "book");
+        assert_eq!(rx1.try_recv().unwrap()["event_type"], "price_change");
+        assert!(rx1.try_recv().is_err());
+        assert!(dispatch(&mut subscribers, None, "PONG").is_empty());
+    }
+
+    #[test]
+    fn test_tick_size_change_updates_cache() {
+        let tick_sizes = TokenCache::new();
+        tick_sizes.insert("1", Decimal::new(1, 2));
+        tick_sizes.insert("2", Decimal::new(1, 2));
+        let mut subscribers = Subscribers::new();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        subscribers.insert("1".into(), vec![tx]);
+
+        let frame = json!([
+            {"event_type": "tick_size_change", "asset_id": "1", "market": "0xcond",
+             "old_tick_size": "0.01", "new_tick_size": "0.001"},
+            {"event_type": "tick_size_change", "asset_id": "2", "new_tick_size": "bad"}
+        ]);
+        dispatch(&mut subscribers, Some(&tick_sizes), &frame.to_string());
+
+        assert_eq!(tick_sizes.get("1"), Some(Decimal::new(1, 3)));
+        assert_eq!(tick_sizes.get("2"), None);
+        let event = TickSizeChangeEvent::deserialize(&rx.try_recv().unwrap()).unwrap();
+        assert_eq!(event.old_tick_size, Decimal::new(1, 2));
+    }
+
+    #[tokio::test]
+    async fn test_resubscribes_after_reconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+
+        let server = tokio::spawn(async move {
+            let mut subscriptions = Vec::new();
+            for round in 0..2 {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut ws = accept_async(stream).await.unwrap();
+                let sub = loop {
+                    match ws.next().await.unwrap().unwrap() {
+                        Message::Text(t) if t != "PING" => break t,
+                        _ => {}
+                    }
+                };
+                subscriptions.push(serde_json::from_str::<Value>(&sub).unwrap());
+
+                let event =
+                    json!([{"event_type": "last_trade_price", "asset_id": "1", "round": round}]);
+                ws.send(Message::Text(event.to_string())).await.unwrap();
+                if round == 1 {
+                    // Hold the connection until the manager closes it.
+                    while let Some(Ok(_)) = ws.next().await {}
+                }
+            }
+            subscriptions
+        });
+
+        let config = WsConfig::default().with_reconnect_delay(Duration::from_millis(10));
+        let manager = WsManager::with_config(&url, config);
+        let mut rx = manager.subscribe("1");
+
+        let first = timeout(WAIT, rx.recv()).await.unwrap().unwrap();
+        assert_eq!(first["round"], 0);
+        let second = timeout(WAIT, rx.recv()).await.unwrap().unwrap();
+        assert_eq!(second["round"], 1);
+
+        drop(manager);
+        let subscriptions = timeout(WAIT, server).await.unwrap().unwrap();
+        // The first subscription after connecting is the handshake too.
+        for subscription in subscriptions {
+            assert_eq!(subscription, json!({"assets_ids": ["1"], "type": "market"}));
+        }
+    }
+}